@@ -0,0 +1,92 @@
+//! Scans the region files of a world and reports every chunk that can't be read.
+//!
+//! usage: `cargo run --bin region_tool -- "saves/World 1.regions" [--repair]`
+//!
+//! With `--repair` broken chunks are dropped from their region, the game generates
+//! them again the next time they are visited. Region files with a broken header
+//! are moved aside to `<name>.corrupt`.
+
+use std::{path::PathBuf, process::ExitCode};
+
+use rpg_game::game_plugins::save::chunk_store::region::{Region, RegionChunkStore};
+
+fn main() -> ExitCode {
+    let mut repair = false;
+    let mut folder = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--repair" => repair = true,
+            _ => folder = Some(PathBuf::from(arg)),
+        }
+    }
+    let Some(folder) = folder else {
+        eprintln!("usage: region_tool <world>.regions [--repair]");
+        return ExitCode::FAILURE;
+    };
+    if !folder.is_dir() {
+        eprintln!("{} is not a folder", folder.display());
+        return ExitCode::FAILURE;
+    }
+
+    let regions = match RegionChunkStore::open(&folder).and_then(|store| store.regions()) {
+        Ok(regions) => regions,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut chunks = 0;
+    let mut broken = 0;
+    for (position, path) in regions.iter() {
+        let (region, problems) = match Region::load(path, *position, true) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("{}", e);
+                broken += 1;
+                continue;
+            }
+        };
+        chunks += region.chunk_count();
+        broken += problems.len();
+
+        let name = path.file_name().unwrap().to_string_lossy();
+        println!(
+            "{}: {} chunks, {} broken",
+            name,
+            region.chunk_count(),
+            problems.len()
+        );
+        for problem in problems.iter() {
+            match problem.chunk {
+                Some(chunk) => println!("    chunk {} {}: {}", chunk.x, chunk.y, problem.message),
+                None => println!("    whole file: {}", problem.message),
+            }
+        }
+
+        if !repair || problems.is_empty() {
+            continue;
+        }
+        let repaired = if problems.iter().any(|problem| problem.chunk.is_none()) {
+            std::fs::rename(path, path.with_extension("corrupt")).map_err(|e| e.to_string())
+        } else {
+            region.write(path)
+        };
+        match repaired {
+            Ok(()) => println!("    repaired"),
+            Err(e) => eprintln!("    repairing failed: {}", e),
+        }
+    }
+
+    println!(
+        "{} regions, {} readable chunks, {} problems",
+        regions.len(),
+        chunks,
+        broken
+    );
+
+    if broken > 0 && !repair {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
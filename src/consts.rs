@@ -14,6 +14,4 @@ pub const RENDER_CHUNK_SIZE: UVec2 = UVec2 {
     y: 2 * 2
 };
 
-pub const NOISE_SCALE: f64 = 12.;
-
-pub const MAX_ZOOM: f32 = 16. * 1.;
\ No newline at end of file
+pub const NOISE_SCALE: f64 = 12.;
\ No newline at end of file
@@ -0,0 +1,228 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::game_plugins::actions::{Action, ActionState};
+
+/// The debug overlay in the top-right corner. Every line is a label and a system
+/// returning its value, registered with `App::add_debug_line`:
+///
+/// ```ignore
+/// app.add_debug_line(DebugGroup::World, "Seed", |rng: Res<RngJesus>| rng.seed.to_string());
+/// ```
+///
+/// The debug action (F12) shows and hides the whole overlay, every group has an action of its own as well.
+pub struct DebugHudPlugin;
+
+impl Plugin for DebugHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugHud>()
+            .add_systems(Startup, spawn_debug_hud)
+            .add_systems(Update, (toggle_debug_hud, show_debug_groups).chain());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugGroup {
+    Performance,
+    Player,
+    World,
+}
+
+impl DebugGroup {
+    // the order the groups are shown in
+    const ALL: [DebugGroup; 3] = [DebugGroup::Performance, DebugGroup::Player, DebugGroup::World];
+
+    fn action(&self) -> Action {
+        match self {
+            DebugGroup::Performance => Action::ToggleDebugPerformance,
+            DebugGroup::Player => Action::ToggleDebugPlayer,
+            DebugGroup::World => Action::ToggleDebugWorld,
+        }
+    }
+}
+
+/// What a debug line shows after its label, plain strings are white.
+pub struct DebugValue {
+    pub text: String,
+    pub color: Color,
+}
+
+impl DebugValue {
+    pub fn colored(text: String, color: Color) -> Self {
+        Self { text, color }
+    }
+}
+
+impl From<String> for DebugValue {
+    fn from(text: String) -> Self {
+        Self::colored(text, Color::WHITE)
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct DebugHud {
+    pub visible: bool,
+    hidden_groups: HashSet<DebugGroup>,
+    // label of every line, the index is what `DebugLine` points at
+    lines: Vec<(DebugGroup, &'static str)>,
+}
+
+impl Default for DebugHud {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            hidden_groups: HashSet::new(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+impl DebugHud {
+    pub fn shows(&self, group: DebugGroup) -> bool {
+        self.visible && !self.hidden_groups.contains(&group)
+    }
+}
+
+pub trait DebugHudApp {
+    /// adds a line to the overlay, `value` only runs while its group is shown
+    fn add_debug_line<O, M>(
+        &mut self,
+        group: DebugGroup,
+        label: &'static str,
+        value: impl IntoSystem<(), O, M>,
+    ) -> &mut Self
+    where
+        O: Into<DebugValue> + 'static;
+}
+
+impl DebugHudApp for App {
+    fn add_debug_line<O, M>(
+        &mut self,
+        group: DebugGroup,
+        label: &'static str,
+        value: impl IntoSystem<(), O, M>,
+    ) -> &mut Self
+    where
+        O: Into<DebugValue> + 'static,
+    {
+        let mut hud = self.world.get_resource_or_insert_with(DebugHud::default);
+        let index = hud.lines.len();
+        hud.lines.push((group, label));
+
+        self.add_systems(
+            Update,
+            value
+                .pipe(move |In(value): In<O>, mut lines: Query<(&DebugLine, &mut Text)>| {
+                    let value: DebugValue = value.into();
+                    for (line, mut text) in &mut lines {
+                        if line.0 == index {
+                            text.sections[1].value = value.text;
+                            text.sections[1].style.color = value.color;
+                            return;
+                        }
+                    }
+                })
+                .run_if(move |hud: Res<DebugHud>| hud.shows(group)),
+        )
+    }
+}
+
+#[derive(Component)]
+struct DebugHudRoot;
+
+#[derive(Component)]
+struct DebugGroupRoot(DebugGroup);
+
+#[derive(Component)]
+struct DebugLine(usize);
+
+/// one column for all groups, hidden groups take no space
+fn spawn_debug_hud(mut commands: Commands, hud: Res<DebugHud>) {
+    let style = TextStyle {
+        font_size: 16.0,
+        color: Color::WHITE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            DebugHudRoot,
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK.with_a(0.5)),
+                z_index: ZIndex::Global(i32::MAX),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Percent(1.),
+                    top: Val::Percent(1.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            Name::new("Debug HUD"),
+        ))
+        .with_children(|root| {
+            for group in DebugGroup::ALL {
+                if !hud.lines.iter().any(|(line_group, _)| *line_group == group) {
+                    continue;
+                }
+
+                root.spawn((
+                    DebugGroupRoot(group),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|group_root| {
+                    for (index, (_, label)) in hud
+                        .lines
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (line_group, _))| *line_group == group)
+                    {
+                        group_root.spawn((
+                            DebugLine(index),
+                            TextBundle::from_sections([
+                                TextSection::new(format!("{}: ", label), style.clone()),
+                                TextSection::new("N/A", style.clone()),
+                            ]),
+                        ));
+                    }
+                });
+            }
+        });
+}
+
+fn toggle_debug_hud(actions: Res<ActionState>, mut hud: ResMut<DebugHud>) {
+    if actions.just_pressed(Action::ToggleDebug) {
+        hud.visible = !hud.visible;
+    }
+
+    for group in DebugGroup::ALL {
+        if actions.just_pressed(group.action()) && !hud.hidden_groups.remove(&group) {
+            hud.hidden_groups.insert(group);
+        }
+    }
+}
+
+fn show_debug_groups(
+    hud: Res<DebugHud>,
+    mut root: Query<&mut Style, (With<DebugHudRoot>, Without<DebugGroupRoot>)>,
+    mut groups: Query<(&DebugGroupRoot, &mut Style)>,
+) {
+    if !hud.is_changed() {
+        return;
+    }
+
+    let display = |shown: bool| if shown { Display::Flex } else { Display::None };
+    for mut style in &mut root {
+        style.display = display(hud.visible);
+    }
+    for (group, mut style) in &mut groups {
+        style.display = display(!hud.hidden_groups.contains(&group.0));
+    }
+}
@@ -0,0 +1,476 @@
+use std::{collections::BTreeMap, fmt, fs};
+
+use bevy::{
+    input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent},
+        InputSystem,
+    },
+    prelude::*,
+    utils::HashSet,
+};
+use serde::{
+    de::{self, value::StrDeserializer, IntoDeserializer, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::data::settings_path;
+
+const BINDINGS_FILE: &str = "bindings.ron";
+// sticks rest a bit off center, anything inside this is no movement at all
+const STICK_DEAD_ZONE: f32 = 0.2;
+// triggers pressed less than this don't zoom
+const TRIGGER_DEAD_ZONE: f32 = 0.1;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bindings::load())
+            .init_resource::<ActionState>()
+            .init_resource::<ActiveGamepad>()
+            .add_systems(
+                PreUpdate,
+                (track_gamepads, update_actions)
+                    .chain()
+                    .in_set(ActionSet)
+                    .after(InputSystem),
+            );
+    }
+}
+
+/// Everything the player can do with a key or a gamepad button. Systems ask
+/// `ActionState` for these instead of looking at keys, so they can be rebound.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    Interact,
+    ZoomIn,
+    ZoomOut,
+    ToggleMap,
+    QuickSave,
+    Pause,
+    ToggleConsole,
+    ToggleDebug,
+    ToggleDebugPerformance,
+    ToggleDebugPlayer,
+    ToggleDebugWorld,
+    ToggleInspector,
+    ToggleNoclip,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Sprint => "Sprint",
+            Action::Interact => "Interact",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::ToggleMap => "Map",
+            Action::QuickSave => "Quick save",
+            Action::Pause => "Pause",
+            Action::ToggleConsole => "Console",
+            Action::ToggleDebug => "Debug overlay",
+            Action::ToggleDebugPerformance => "Debug performance",
+            Action::ToggleDebugPlayer => "Debug player",
+            Action::ToggleDebugWorld => "Debug world",
+            Action::ToggleInspector => "Inspector",
+            Action::ToggleNoclip => "Noclip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    // the button on any connected gamepad
+    Button(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn is_key(&self) -> bool {
+        matches!(self, Binding::Key(_))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Button(button) => format!("Pad {:?}", button),
+        }
+    }
+}
+
+/// Which keys and buttons trigger which action, stored in `settings/bindings.ron`.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    #[serde(deserialize_with = "known_actions")]
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+}
+
+/// actions that were removed since the file was written are dropped, instead of
+/// throwing away every binding of the player
+fn known_actions<'de, D>(deserializer: D) -> Result<BTreeMap<Action, Vec<Binding>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let stored = BTreeMap::<ActionName, Vec<Binding>>::deserialize(deserializer)?;
+
+    let actions = stored
+        .into_iter()
+        .filter_map(|(name, bindings)| {
+            let name: StrDeserializer<de::value::Error> = name.0.as_str().into_deserializer();
+            match Action::deserialize(name) {
+                Ok(action) => Some((action, bindings)),
+                Err(e) => {
+                    warn!("dropping bindings: {}", e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    Ok(actions)
+}
+
+// an action in the bindings file as it was written, it might not exist anymore
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct ActionName(String);
+
+impl<'de> Deserialize<'de> for ActionName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NameVisitor;
+
+        impl<'de> Visitor<'de> for NameVisitor {
+            type Value = ActionName;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("the name of an action")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<ActionName, E> {
+                Ok(ActionName(name.to_string()))
+            }
+        }
+
+        deserializer.deserialize_identifier(NameVisitor)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::{Button, Key};
+
+        let actions = Action::iter()
+            .map(|action| {
+                let bindings = match action {
+                    Action::MoveUp => vec![Key(KeyCode::W), Button(GamepadButtonType::DPadUp)],
+                    Action::MoveDown => vec![Key(KeyCode::S), Button(GamepadButtonType::DPadDown)],
+                    Action::MoveLeft => vec![Key(KeyCode::A), Button(GamepadButtonType::DPadLeft)],
+                    Action::MoveRight => {
+                        vec![Key(KeyCode::D), Button(GamepadButtonType::DPadRight)]
+                    }
+                    Action::Sprint => vec![
+                        Key(KeyCode::ShiftLeft),
+                        Key(KeyCode::ShiftRight),
+                        Button(GamepadButtonType::West),
+                    ],
+                    Action::Interact => vec![Key(KeyCode::Space), Button(GamepadButtonType::South)],
+                    Action::ZoomIn => vec![
+                        Key(KeyCode::Plus),
+                        Key(KeyCode::Equals),
+                        Key(KeyCode::NumpadAdd),
+                        Button(GamepadButtonType::RightTrigger),
+                    ],
+                    Action::ZoomOut => vec![
+                        Key(KeyCode::Minus),
+                        Key(KeyCode::NumpadSubtract),
+                        Button(GamepadButtonType::LeftTrigger),
+                    ],
+                    Action::ToggleMap => vec![Key(KeyCode::M), Button(GamepadButtonType::Select)],
+                    Action::QuickSave => vec![Key(KeyCode::F5)],
+                    Action::Pause => vec![Key(KeyCode::Escape), Button(GamepadButtonType::Start)],
+                    Action::ToggleConsole => vec![Key(KeyCode::Grave)],
+                    Action::ToggleDebug => vec![Key(KeyCode::F12)],
+                    Action::ToggleDebugPerformance => vec![Key(KeyCode::F9)],
+                    Action::ToggleDebugPlayer => vec![Key(KeyCode::F10)],
+                    Action::ToggleDebugWorld => vec![Key(KeyCode::F11)],
+                    Action::ToggleInspector => vec![Key(KeyCode::F2)],
+                    Action::ToggleNoclip => vec![Key(KeyCode::F3)],
+                };
+                (action, bindings)
+            })
+            .collect();
+
+        Self { actions }
+    }
+}
+
+impl Bindings {
+    /// the bindings file of the player, the defaults if there is none or it is broken
+    pub fn load() -> Self {
+        let path = settings_path().join(BINDINGS_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        let mut bindings = match ron::from_str::<Bindings>(&content) {
+            Ok(bindings) => bindings,
+            Err(e) => {
+                error!("can't parse {}, using the default bindings: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+
+        // actions added after the file was written get their default bindings
+        for (action, defaults) in Self::default().actions {
+            bindings.actions.entry(action).or_insert(defaults);
+        }
+        for (binding, actions) in bindings.conflicts() {
+            warn!("{} is bound to {:?}", binding.name(), actions);
+        }
+
+        bindings
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let folder = settings_path();
+        fs::create_dir_all(&folder)
+            .map_err(|e| format!("can't create {}: {}", folder.display(), e))?;
+
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("can't serialize bindings: {}", e))?;
+        let path = folder.join(BINDINGS_FILE);
+        fs::write(&path, content).map_err(|e| format!("can't write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    /// the keyboard keys of `action`, for things that read the keyboard before the actions
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.get(action).iter().filter_map(|binding| match binding {
+            Binding::Key(key) => Some(*key),
+            Binding::Button(_) => None,
+        })
+    }
+
+    /// replaces the first key of `action` with a key, or its first button with a button.
+    /// Other actions keep theirs, that is what `conflicts` is for.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.actions.entry(action).or_default();
+        bindings.retain(|bound| *bound != binding);
+        match bindings.iter().position(|bound| bound.is_key() == binding.is_key()) {
+            Some(index) => bindings[index] = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    /// every key or button that triggers more than one action, with those actions
+    pub fn conflicts(&self) -> Vec<(Binding, Vec<Action>)> {
+        let mut by_binding: Vec<(Binding, Vec<Action>)> = Vec::new();
+        for (action, bindings) in self.actions.iter() {
+            for binding in bindings {
+                match by_binding.iter_mut().find(|(bound, _)| bound == binding) {
+                    Some((_, actions)) => actions.push(*action),
+                    None => by_binding.push((*binding, vec![*action])),
+                }
+            }
+        }
+
+        by_binding.retain(|(_, actions)| actions.len() > 1);
+        by_binding
+    }
+
+    pub fn in_conflict(&self, action: Action) -> bool {
+        self.conflicts()
+            .iter()
+            .any(|(_, actions)| actions.contains(&action))
+    }
+
+    /// whether one of the bindings of `action` is held down right now
+    pub fn pressed(
+        &self,
+        action: Action,
+        keys: &Input<KeyCode>,
+        gamepads: &Gamepads,
+        buttons: &Input<GamepadButton>,
+    ) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            Binding::Key(key) => keys.pressed(*key),
+            Binding::Button(button_type) => gamepads
+                .iter()
+                .any(|gamepad| buttons.pressed(GamepadButton::new(gamepad, *button_type))),
+        })
+    }
+}
+
+/// Updated right after the input is read, everything before it can still take keys away.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionSet;
+
+/// The gamepad whose sticks and triggers are read, the one that was connected last.
+/// Buttons work on every connected gamepad.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+/// The actions that are held down this frame.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    // left stick of the active gamepad, dead zone already taken out
+    stick: Vec2,
+    // right trigger minus left trigger, positive zooms in
+    zoom: f32,
+}
+
+impl ActionState {
+    /// where the player wants to go, no longer than 1. The stick moves slower
+    /// the less it is pushed, the move keys always go full speed.
+    pub fn movement(&self) -> Vec2 {
+        let mut keys = Vec2::ZERO;
+        if self.pressed(Action::MoveUp) {
+            keys.y += 1.;
+        }
+        if self.pressed(Action::MoveDown) {
+            keys.y -= 1.;
+        }
+        if self.pressed(Action::MoveRight) {
+            keys.x += 1.;
+        }
+        if self.pressed(Action::MoveLeft) {
+            keys.x -= 1.;
+        }
+
+        combine_movement(keys, self.stick)
+    }
+
+    /// how far the triggers are pressed, from -1 (zoom out) to 1 (zoom in)
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    fn set(&mut self, action: Action, pressed: bool) {
+        if pressed {
+            if self.pressed.insert(action) {
+                self.just_pressed.insert(action);
+            }
+        } else {
+            self.pressed.remove(&action);
+        }
+    }
+}
+
+/// key directions count as fully pushed, diagonals included, whichever input pushes further wins
+pub fn combine_movement(keys: Vec2, stick: Vec2) -> Vec2 {
+    let keys = keys.normalize_or_zero();
+    let direction = if stick.length() > keys.length() {
+        stick
+    } else {
+        keys
+    };
+    direction.clamp_length_max(1.)
+}
+
+/// takes the dead zone out of a stick, so the stick goes from 0 right outside of it to 1 at the edge
+pub fn apply_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= dead_zone {
+        return Vec2::ZERO;
+    }
+
+    let scaled = ((length - dead_zone) / (1. - dead_zone)).min(1.);
+    stick / length * scaled
+}
+
+/// gamepads can come and go at any time, the newest one takes over the sticks
+fn track_gamepads(
+    mut connections: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for event in connections.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("gamepad {} connected: {}", event.gamepad.id, info.name);
+                active.0 = Some(event.gamepad);
+            }
+            GamepadConnection::Disconnected => {
+                info!("gamepad {} disconnected", event.gamepad.id);
+                if active.0 == Some(event.gamepad) {
+                    active.0 = gamepads.iter().find(|gamepad| *gamepad != event.gamepad);
+                }
+            }
+        }
+    }
+}
+
+fn update_actions(
+    bindings: Res<Bindings>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    active: Res<ActiveGamepad>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    analog_buttons: Res<Axis<GamepadButton>>,
+    mut state: ResMut<ActionState>,
+) {
+    state.just_pressed.clear();
+    for action in Action::iter() {
+        state.set(action, bindings.pressed(action, &keys, &gamepads, &buttons));
+    }
+
+    let Some(gamepad) = active.0 else {
+        state.stick = Vec2::ZERO;
+        state.zoom = 0.;
+        return;
+    };
+
+    let axis = |axis_type| axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+    let stick = Vec2::new(axis(GamepadAxisType::LeftStickX), axis(GamepadAxisType::LeftStickY));
+    state.stick = apply_dead_zone(stick, STICK_DEAD_ZONE);
+
+    let trigger = |button_type| {
+        let pressed = analog_buttons
+            .get(GamepadButton::new(gamepad, button_type))
+            .unwrap_or(0.);
+        if pressed < TRIGGER_DEAD_ZONE {
+            0.
+        } else {
+            pressed
+        }
+    };
+    state.zoom = trigger(GamepadButtonType::RightTrigger2) - trigger(GamepadButtonType::LeftTrigger2);
+}
+
+/// like `input_toggle_active`, but for an action
+pub fn action_toggle_active(
+    default: bool,
+    action: Action,
+) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    let mut active = default;
+    move |actions: Res<ActionState>| {
+        active ^= actions.just_pressed(action);
+        active
+    }
+}
@@ -0,0 +1,323 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use super::{
+    data::load_data,
+    movement::Velocity,
+    world_map::{
+        chunk_gen::{BiomTiles, TileType},
+        utils::tile_at_world_pos,
+        world_gen::RngJesus,
+    },
+};
+use crate::game_state::GameState;
+
+// anything slower than this stands still as far as the animation is concerned
+const WALKING_SPEED: f32 = 5.;
+
+/// Plays the sprite sheet animations of `assets/data/animations.ron`. Anything with a
+/// `SpriteAnimation` and a `Velocity` walks, idles and swims on its own, other clips
+/// are started with `SpriteAnimation::play`.
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SpriteAnimation>()
+            // before `Startup`, so whatever spawns there can use the sheets
+            .add_systems(PreStartup, load_sprite_sheets)
+            .add_systems(
+                Update,
+                (pick_clips, animate_sprites)
+                    .chain()
+                    .run_if(in_state(GameState::GAME)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect, Deserialize)]
+pub enum Clip {
+    #[default]
+    Idle,
+    Walk,
+    Swim,
+    Chop,
+}
+
+/// The way a sprite looks, every clip has a row on the sheet for each of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum Facing {
+    #[default]
+    Down,
+    Left,
+    Right,
+    Up,
+}
+
+impl Facing {
+    /// the side something moving along `velocity` shows, the stronger axis wins
+    pub fn of(velocity: Vec2) -> Facing {
+        if velocity.x.abs() > velocity.y.abs() {
+            if velocity.x > 0. {
+                Facing::Right
+            } else {
+                Facing::Left
+            }
+        } else if velocity.y > 0. {
+            Facing::Up
+        } else {
+            Facing::Down
+        }
+    }
+
+    fn row(&self) -> usize {
+        match self {
+            Facing::Down => 0,
+            Facing::Left => 1,
+            Facing::Right => 2,
+            Facing::Up => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ClipDef {
+    // the row of the down facing frames, the other directions follow
+    pub row: usize,
+    pub frames: usize,
+    pub fps: f32,
+    pub repeat: bool,
+}
+
+/// One sprite sheet as written in `assets/data/animations.ron`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpriteSheetDef {
+    pub image: String,
+    pub frame_size: (u32, u32),
+    pub columns: usize,
+    pub rows: usize,
+    pub clips: HashMap<Clip, ClipDef>,
+}
+
+impl SpriteSheetDef {
+    /// the clip to show for `clip`, sheets without it fall back to idle
+    pub fn clip(&self, clip: Clip) -> Option<&ClipDef> {
+        self.clips.get(&clip).or_else(|| self.clips.get(&Clip::Idle))
+    }
+
+    /// whether every clip fits on the sheet, with a row for every facing
+    pub fn validate(&self) -> Result<(), String> {
+        for (clip, def) in self.clips.iter() {
+            if def.frames == 0 || def.frames > self.columns {
+                return Err(format!(
+                    "{:?} has {} frames but the sheet is {} columns wide",
+                    clip, def.frames, self.columns
+                ));
+            }
+            if def.row + 4 > self.rows {
+                return Err(format!(
+                    "{:?} needs rows {} to {} but the sheet has {}",
+                    clip,
+                    def.row,
+                    def.row + 3,
+                    self.rows
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// where `frame` of `clip` is in the texture atlas
+    pub fn index(&self, clip: Clip, facing: Facing, frame: usize) -> usize {
+        let Some(def) = self.clip(clip) else {
+            return 0;
+        };
+        (def.row + facing.row()) * self.columns + frame.min(def.frames.saturating_sub(1))
+    }
+}
+
+/// Every loaded sprite sheet with its texture atlas, by name.
+#[derive(Resource, Debug, Default)]
+pub struct SpriteSheets {
+    defs: HashMap<String, SpriteSheetDef>,
+    atlases: HashMap<String, Handle<TextureAtlas>>,
+}
+
+impl SpriteSheets {
+    pub fn get(&self, name: &str) -> Option<&SpriteSheetDef> {
+        self.defs.get(name)
+    }
+
+    /// a sprite showing the first idle frame of `name`, `None` if there is no such sheet
+    pub fn bundle(&self, name: &str, transform: Transform) -> Option<SpriteSheetBundle> {
+        let def = self.defs.get(name)?;
+        Some(SpriteSheetBundle {
+            texture_atlas: self.atlases.get(name)?.clone(),
+            sprite: TextureAtlasSprite::new(def.index(Clip::Idle, Facing::Down, 0)),
+            transform,
+            ..default()
+        })
+    }
+}
+
+/// Which clip of which sheet an entity shows and how far it is into it.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct SpriteAnimation {
+    pub sheet: String,
+    pub facing: Facing,
+    clip: Clip,
+    frame: usize,
+    // seconds since the last frame
+    elapsed: f32,
+    // a clip that doesn't repeat is on its last frame
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(sheet: impl Into<String>) -> Self {
+        Self {
+            sheet: sheet.into(),
+            facing: Facing::Down,
+            clip: Clip::Idle,
+            frame: 0,
+            elapsed: 0.,
+            finished: false,
+        }
+    }
+
+    pub fn clip(&self) -> Clip {
+        self.clip
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// switches to `clip`, from the start. Playing the clip that is already on keeps it going,
+    /// unless it was done.
+    pub fn play(&mut self, clip: Clip) {
+        if self.clip == clip && !self.finished {
+            return;
+        }
+        self.clip = clip;
+        self.frame = 0;
+        self.elapsed = 0.;
+        self.finished = false;
+    }
+
+    /// a clip that plays once hasn't reached its end yet, nothing else may take over
+    pub fn is_busy(&self, sheet: &SpriteSheetDef) -> bool {
+        sheet.clip(self.clip).is_some_and(|clip| !clip.repeat) && !self.finished
+    }
+
+    pub fn advance(&mut self, sheet: &SpriteSheetDef, seconds: f32) {
+        let Some(clip) = sheet.clip(self.clip) else {
+            return;
+        };
+        if self.finished || clip.fps <= 0. {
+            return;
+        }
+
+        self.elapsed += seconds;
+        let frame_time = 1. / clip.fps;
+        while self.elapsed >= frame_time {
+            self.elapsed -= frame_time;
+            if self.frame + 1 < clip.frames {
+                self.frame += 1;
+            } else if clip.repeat {
+                self.frame = 0;
+            } else {
+                self.finished = true;
+                return;
+            }
+        }
+    }
+}
+
+fn load_sprite_sheets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let mut defs = match load_data::<HashMap<String, SpriteSheetDef>>("animations.ron") {
+        Ok(defs) => defs,
+        Err(e) => {
+            error!("no sprite sheets loaded, everything stays still: {}", e);
+            HashMap::default()
+        }
+    };
+    // a broken sheet would show frames of other clips or panic, better to not have it at all
+    defs.retain(|name, def| match def.validate() {
+        Ok(()) => true,
+        Err(e) => {
+            error!("sprite sheet {:?} is left out: {}", name, e);
+            false
+        }
+    });
+
+    let atlases = defs
+        .iter()
+        .map(|(name, def)| {
+            let atlas = TextureAtlas::from_grid(
+                asset_server.load(&def.image),
+                Vec2::new(def.frame_size.0 as f32, def.frame_size.1 as f32),
+                def.columns,
+                def.rows,
+                None,
+                None,
+            );
+            (name.clone(), atlases.add(atlas))
+        })
+        .collect();
+
+    commands.insert_resource(SpriteSheets { defs, atlases });
+}
+
+/// moving things walk towards where they go and swim in deep water, still things idle
+fn pick_clips(
+    sheets: Res<SpriteSheets>,
+    rng_jesus: Res<RngJesus>,
+    mut animated: Query<(&mut SpriteAnimation, &Velocity, &Transform)>,
+) {
+    for (mut animation, velocity, transform) in &mut animated {
+        let Some(sheet) = sheets.get(&animation.sheet) else {
+            continue;
+        };
+        if animation.is_busy(sheet) {
+            continue;
+        }
+
+        let moving = velocity.0.length() > WALKING_SPEED;
+        if moving {
+            animation.facing = Facing::of(velocity.0);
+        }
+
+        let tile = tile_at_world_pos(transform.translation.truncate());
+        let clip = if rng_jesus.get_tile_type_at(&tile) == TileType::DeepWater {
+            Clip::Swim
+        } else if moving {
+            Clip::Walk
+        } else {
+            Clip::Idle
+        };
+        animation.play(clip);
+    }
+}
+
+fn animate_sprites(
+    time: Res<Time>,
+    sheets: Res<SpriteSheets>,
+    mut animated: Query<(&mut SpriteAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (mut animation, mut sprite) in &mut animated {
+        let Some(sheet) = sheets.get(&animation.sheet) else {
+            continue;
+        };
+
+        animation.advance(sheet, time.delta_seconds());
+        let index = sheet.index(animation.clip, animation.facing, animation.frame);
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
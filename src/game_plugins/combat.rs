@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use super::{
+    enemy::Enemy,
+    inventory::{Inventory, Item},
+    movement::Velocity,
+    player::{InteractSet, Player, PlayerInteract, PlayerTeleported},
+    sound::{PlaySfx, Sfx},
+};
+use crate::game_state::GameState;
+
+const PLAYER_ATTACK_RANGE: f32 = 28.;
+const PLAYER_ATTACK_DAMAGE: u32 = 5;
+pub const PLAYER_MAX_HEALTH: u32 = 20;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (player_attack.in_set(InteractSet::Enemy), player_death)
+                .run_if(in_state(GameState::GAME)),
+        )
+            .register_type::<Health>();
+    }
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// returns true once there is no health left
+    pub fn damage(&mut self, amount: u32) -> bool {
+        self.current = self.current.saturating_sub(amount);
+        self.is_dead()
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+}
+
+/// hits the closest enemy in reach, the same key press that chops trees
+fn player_attack(
+    mut interact: ResMut<PlayerInteract>,
+    player: Query<&Inventory, With<Player>>,
+    mut enemies: Query<(&Transform, &mut Health), With<Enemy>>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    let Some(position) = interact.position() else {
+        return;
+    };
+
+    let closest = enemies
+        .iter_mut()
+        .map(|(transform, health)| {
+            let enemy = transform.translation.truncate();
+            (enemy, health, enemy.distance(position))
+        })
+        .filter(|(_, _, distance)| *distance <= PLAYER_ATTACK_RANGE)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+    let Some((enemy, mut health, _)) = closest else {
+        return;
+    };
+    interact.consume();
+
+    let damage = if player.single().has(Item::Axe) {
+        PLAYER_ATTACK_DAMAGE * 2
+    } else {
+        PLAYER_ATTACK_DAMAGE
+    };
+
+    health.damage(damage);
+    sfx.send(PlaySfx::at(Sfx::Hit, enemy));
+}
+
+/// a dead player wakes up again at the world origin
+fn player_death(
+    mut sfx: EventWriter<PlaySfx>,
+    mut teleported: EventWriter<PlayerTeleported>,
+    mut player: Query<(&mut Transform, &mut Velocity, &mut Health), With<Player>>,
+) {
+    let (mut transform, mut velocity, mut health) = player.single_mut();
+    if !health.is_dead() {
+        return;
+    }
+
+    info!("you died at {:?}", transform.translation);
+    health.current = health.max;
+    transform.translation.x = 0.;
+    transform.translation.y = 0.;
+    *velocity = Velocity::default();
+    teleported.send(PlayerTeleported);
+
+    sfx.send(PlaySfx::new(Sfx::PlayerDeath));
+}
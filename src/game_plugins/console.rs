@@ -0,0 +1,373 @@
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    utils::HashMap,
+    window::ReceivedCharacter,
+};
+
+use super::actions::{Action, ActionSet, Bindings};
+use crate::game_state::GameState;
+
+// lines kept in the console, older ones scroll away
+const CONSOLE_LINES: usize = 200;
+// lines shown at once
+const CONSOLE_VISIBLE_LINES: usize = 14;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .add_event::<ConsoleCommand>()
+            .add_systems(Startup, setup_console)
+            // right after the keyboard is read, so the keys typed into the console
+            // can be taken away from everything else
+            .add_systems(
+                PreUpdate,
+                console_input.after(InputSystem).before(ActionSet),
+            )
+            .add_systems(Update, draw_console)
+            .add_systems(OnEnter(GameState::MENU), close_console)
+            .add_systems(OnEnter(GameState::PAUSED), close_console)
+            .add_console_command("help", "help - lists every command", help_command)
+            .add_console_command("clear", "clear - empties the console", clear_command);
+    }
+}
+
+/// A line typed into the console that matched a registered command.
+#[derive(Event, Debug, Clone)]
+pub struct ConsoleCommand {
+    // the registered name, it can be more than one word like "spawn pig"
+    pub name: &'static str,
+    pub args: Vec<String>,
+}
+
+impl ConsoleCommand {
+    pub fn is(&self, name: &str) -> bool {
+        self.name == name
+    }
+
+    /// the argument at `index` parsed as `T`
+    pub fn arg<T: std::str::FromStr>(&self, index: usize) -> Option<T> {
+        self.args.get(index)?.parse().ok()
+    }
+}
+
+/// Every command the console knows, with a usage line for `help`.
+#[derive(Resource, Debug, Default)]
+pub struct ConsoleCommands {
+    usages: HashMap<&'static str, &'static str>,
+}
+
+impl ConsoleCommands {
+    pub fn register(&mut self, name: &'static str, usage: &'static str) {
+        if self.usages.insert(name, usage).is_some() {
+            warn!("console command {:?} was registered twice", name);
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.usages.keys().copied().collect();
+        names.sort();
+        names
+    }
+
+    /// finds the command `line` starts with, the one with the most words wins
+    pub fn parse(&self, line: &str) -> Result<ConsoleCommand, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let name = self
+            .usages
+            .keys()
+            .filter(|name| {
+                let name_words: Vec<&str> = name.split_whitespace().collect();
+                words.starts_with(&name_words)
+            })
+            .max_by_key(|name| name.split_whitespace().count())
+            .ok_or_else(|| format!("unknown command {:?}, try help", line.trim()))?;
+
+        Ok(ConsoleCommand {
+            name,
+            args: words[name.split_whitespace().count()..]
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+        })
+    }
+
+    /// the commands that start with what was typed so far
+    pub fn complete(&self, input: &str) -> Vec<&'static str> {
+        let input = input.trim_start();
+        self.names()
+            .into_iter()
+            .filter(|name| name.starts_with(input))
+            .collect()
+    }
+}
+
+/// The console window: what is typed, what was printed and what was typed before.
+#[derive(Resource, Debug, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    lines: Vec<String>,
+    history: Vec<String>,
+    // the history entry shown in the input while going through it with up and down
+    browsing: Option<usize>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        if self.lines.len() > CONSOLE_LINES {
+            self.lines.remove(0);
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// takes the input, remembers it and hands back what is to be run
+    pub fn submit(&mut self) -> Option<String> {
+        self.browsing = None;
+        let line = std::mem::take(&mut self.input);
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        self.print(format!("> {}", line));
+        // typing the same thing again and again shouldn't fill the history
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        Some(line)
+    }
+
+    /// the entry typed before the one shown, up arrow
+    pub fn history_back(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match self.browsing {
+            Some(index) => index.saturating_sub(1),
+            None => self.history.len() - 1,
+        };
+        self.browsing = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    /// the entry typed after the one shown, down arrow. Past the newest the input is empty again.
+    pub fn history_forward(&mut self) {
+        let Some(index) = self.browsing else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.browsing = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        } else {
+            self.browsing = None;
+            self.input.clear();
+        }
+    }
+
+    /// completes the input as far as the matching commands agree, lists them if there are more
+    pub fn autocomplete(&mut self, commands: &ConsoleCommands) {
+        let candidates = commands.complete(&self.input);
+        match candidates.as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |common, name| {
+                    first
+                        .chars()
+                        .zip(name.chars())
+                        .take(common)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                self.input = first[..common].to_string();
+                self.print(candidates.join("  "));
+            }
+        }
+    }
+}
+
+pub trait ConsoleApp {
+    /// registers `name` with the console and adds `handler`, a system reading
+    /// `ConsoleCommand` events. It only runs in frames where a command was typed.
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: impl IntoSystemConfigs<M>,
+    ) -> &mut Self;
+}
+
+impl ConsoleApp for App {
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: impl IntoSystemConfigs<M>,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .register(name, usage);
+
+        self.add_systems(
+            Update,
+            handler.run_if(on_event::<ConsoleCommand>()),
+        )
+    }
+}
+
+/// the commands named `name` that were typed this frame
+pub fn typed<'a>(
+    commands: &'a mut EventReader<ConsoleCommand>,
+    name: &'static str,
+) -> impl Iterator<Item = &'a ConsoleCommand> {
+    commands.read().filter(move |command| command.is(name))
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn setup_console(mut commands: Commands) {
+    commands
+        .spawn((
+            ConsoleRoot,
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK.with_a(0.8)),
+                z_index: ZIndex::Global(i32::MAX),
+                visibility: Visibility::Hidden,
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.),
+                    left: Val::Px(0.),
+                    width: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::FlexEnd,
+                    padding: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                ..default()
+            },
+            Name::new("Console"),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                ConsoleText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn close_console(mut console: ResMut<Console>) {
+    console.open = false;
+}
+
+/// the console key (backtick) opens the console in game. While it is open it gets every key, nothing
+/// else sees them, so typing doesn't walk the player around.
+fn console_input(
+    state: Res<State<GameState>>,
+    bindings: Res<Bindings>,
+    commands: Res<ConsoleCommands>,
+    mut console: ResMut<Console>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut sent: EventWriter<ConsoleCommand>,
+) {
+    if !console.open {
+        characters.clear();
+        if *state.get() == GameState::GAME
+            && keys.any_just_pressed(bindings.keys(Action::ToggleConsole))
+        {
+            console.open = true;
+            keys.reset_all();
+        }
+        return;
+    }
+
+    if keys.any_just_pressed(bindings.keys(Action::ToggleConsole).chain([KeyCode::Escape])) {
+        console.open = false;
+        characters.clear();
+    } else if keys.just_pressed(KeyCode::Return) {
+        if let Some(line) = console.submit() {
+            match commands.parse(&line) {
+                Ok(command) => sent.send(command),
+                Err(e) => console.print(e),
+            }
+        }
+    } else if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    } else if keys.just_pressed(KeyCode::Up) {
+        console.history_back();
+    } else if keys.just_pressed(KeyCode::Down) {
+        console.history_forward();
+    } else if keys.just_pressed(KeyCode::Tab) {
+        console.autocomplete(&commands);
+    }
+
+    for character in characters.read() {
+        if !character.char.is_control() {
+            console.input.push(character.char);
+        }
+    }
+
+    keys.reset_all();
+}
+
+fn draw_console(
+    console: Res<Console>,
+    mut root: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut text: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    *root.single_mut() = if console.open {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    let lines = console.lines();
+    let shown = &lines[lines.len().saturating_sub(CONSOLE_VISIBLE_LINES)..];
+    let mut content = shown.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str(&format!("> {}_", console.input));
+    text.single_mut().sections[0].value = content;
+}
+
+fn help_command(
+    mut commands: EventReader<ConsoleCommand>,
+    registered: Res<ConsoleCommands>,
+    mut console: ResMut<Console>,
+) {
+    for _ in typed(&mut commands, "help") {
+        for name in registered.names() {
+            console.print(registered.usages[name]);
+        }
+    }
+}
+
+fn clear_command(mut commands: EventReader<ConsoleCommand>, mut console: ResMut<Console>) {
+    for _ in typed(&mut commands, "clear") {
+        console.lines.clear();
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+
+/// same lookup bevy's asset server does, so data files are found next to the other assets
+pub fn assets_path() -> PathBuf {
+    if let Ok(root) = std::env::var("BEVY_ASSET_ROOT") {
+        return PathBuf::from(root).join("assets");
+    }
+    if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+        return PathBuf::from(manifest_dir).join("assets");
+    }
+
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("assets")))
+        .unwrap_or_else(|| PathBuf::from("assets"))
+}
+
+/// where the save games live, right next to the assets folder
+pub fn saves_path() -> PathBuf {
+    assets_path().with_file_name("saves")
+}
+
+/// where the player's own settings live, next to the saves
+pub fn settings_path() -> PathBuf {
+    assets_path().with_file_name("settings")
+}
+
+/// reads a ron file from `assets/data`
+pub fn load_data<T: DeserializeOwned>(file: &str) -> Result<T, String> {
+    let path = assets_path().join("data").join(file);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+
+    ron::from_str(&content).map_err(|e| format!("can't parse {}: {}", path.display(), e))
+}
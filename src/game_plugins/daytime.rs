@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    console::{typed, Console, ConsoleApp, ConsoleCommand},
+    seasons::Season,
+};
+use crate::game_state::GameState;
+
+// a new world starts in the morning
+const START_HOUR: f32 = 8.;
+const DEFAULT_DAY_LENGTH: f32 = 600.;
+// the choices in the settings menu, in seconds
+pub const DAY_LENGTHS: [f32; 4] = [300., 600., 1200., 2400.];
+
+// (hour, overlay color) the tint is blended between
+const TINT_KEYS: [(f32, Color); 8] = [
+    (0., Color::rgba(0.02, 0.03, 0.15, 0.6)),
+    (5., Color::rgba(0.02, 0.03, 0.15, 0.6)),
+    (6., Color::rgba(0.9, 0.45, 0.3, 0.25)),
+    (7., Color::rgba(0.9, 0.8, 0.6, 0.)),
+    (18., Color::rgba(0.9, 0.8, 0.6, 0.)),
+    (19., Color::rgba(0.85, 0.35, 0.2, 0.25)),
+    (20., Color::rgba(0.02, 0.03, 0.15, 0.6)),
+    (24., Color::rgba(0.02, 0.03, 0.15, 0.6)),
+];
+
+pub struct DaytimePlugin;
+
+impl Plugin for DaytimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldClock>()
+            .init_resource::<ClockSettings>()
+            .add_event::<DayStarted>()
+            .add_event::<NightStarted>()
+            .add_systems(Startup, setup_tint)
+            .add_systems(OnEnter(GameState::MENU), (reset_clock, hide_tint))
+            .add_systems(
+                Update,
+                (tick_clock, update_tint)
+                    .chain()
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_console_command(
+                "time set",
+                "time set <hour> - moves the clock within the day",
+                time_set_command,
+            )
+            .register_type::<WorldClock>();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayPhase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+/// The time in the world, it only runs while playing.
+#[derive(Resource, Debug, Clone, Reflect, Serialize, Deserialize)]
+pub struct WorldClock {
+    pub day: u32,
+    // 0..24
+    pub hour: f32,
+    // whether the last `tick_clock` saw the night, `None` right after the clock was set,
+    // so the first tick tells everyone what time of day it is
+    #[serde(skip)]
+    #[reflect(ignore)]
+    was_night: Option<bool>,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self::new(1, START_HOUR)
+    }
+}
+
+impl WorldClock {
+    pub fn new(day: u32, hour: f32) -> Self {
+        Self {
+            day,
+            hour,
+            was_night: None,
+        }
+    }
+
+    pub fn phase(&self) -> DayPhase {
+        match self.hour {
+            hour if hour < 5. => DayPhase::Night,
+            hour if hour < 7. => DayPhase::Dawn,
+            hour if hour < 18. => DayPhase::Day,
+            hour if hour < 20. => DayPhase::Dusk,
+            _ => DayPhase::Night,
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.phase() == DayPhase::Night
+    }
+
+    pub fn season(&self) -> Season {
+        Season::of_day(self.day)
+    }
+
+    /// like "Day 3 14:05"
+    pub fn label(&self) -> String {
+        let minutes = (self.hour * 60.) as u32;
+        format!("Day {} {:02}:{:02}", self.day, minutes / 60, minutes % 60)
+    }
+
+    /// the color laid over the world at this time of day
+    pub fn tint(&self) -> Color {
+        let (from, to) = TINT_KEYS
+            .windows(2)
+            .map(|keys| (keys[0], keys[1]))
+            .find(|((_, _), (hour, _))| self.hour < *hour)
+            .unwrap_or((TINT_KEYS[6], TINT_KEYS[7]));
+
+        let t = ((self.hour - from.0) / (to.0 - from.0)).clamp(0., 1.);
+        let (a, b) = (from.1.as_rgba_f32(), to.1.as_rgba_f32());
+        Color::rgba(
+            a[0] + (b[0] - a[0]) * t,
+            a[1] + (b[1] - a[1]) * t,
+            a[2] + (b[2] - a[2]) * t,
+            a[3] + (b[3] - a[3]) * t,
+        )
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct ClockSettings {
+    // real seconds one whole day takes
+    pub day_length: f32,
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        Self {
+            day_length: DEFAULT_DAY_LENGTH,
+        }
+    }
+}
+
+impl ClockSettings {
+    pub fn next_day_length(&self) -> f32 {
+        DAY_LENGTHS
+            .into_iter()
+            .find(|length| *length > self.day_length)
+            .unwrap_or(DAY_LENGTHS[0])
+    }
+}
+
+/// Sent when the night is over, at the start of dawn.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DayStarted {
+    pub day: u32,
+}
+
+/// Sent when the night begins.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct NightStarted {
+    pub day: u32,
+}
+
+/// Full screen node below the rest of the UI that tints the whole world.
+#[derive(Component)]
+struct DaylightTint;
+
+fn setup_tint(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            background_color: BackgroundColor(Color::NONE),
+            z_index: ZIndex::Global(i32::MIN),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            ..default()
+        },
+        DaylightTint,
+        Name::new("Daylight Tint"),
+    ));
+}
+
+fn reset_clock(mut clock: ResMut<WorldClock>) {
+    *clock = WorldClock::default();
+}
+
+fn hide_tint(mut tint: Query<&mut BackgroundColor, With<DaylightTint>>) {
+    for mut color in &mut tint {
+        *color = BackgroundColor(Color::NONE);
+    }
+}
+
+fn tick_clock(
+    time: Res<Time>,
+    settings: Res<ClockSettings>,
+    mut clock: ResMut<WorldClock>,
+    mut day_started: EventWriter<DayStarted>,
+    mut night_started: EventWriter<NightStarted>,
+) {
+    clock.hour += time.delta_seconds() / settings.day_length * 24.;
+    if clock.hour >= 24. {
+        clock.hour -= 24.;
+        clock.day += 1;
+    }
+
+    let is_night = clock.is_night();
+    if clock.was_night == Some(is_night) {
+        return;
+    }
+    clock.was_night = Some(is_night);
+
+    if is_night {
+        info!("night falls on day {}", clock.day);
+        night_started.send(NightStarted { day: clock.day });
+    } else {
+        info!("day {} begins", clock.day);
+        day_started.send(DayStarted { day: clock.day });
+    }
+}
+
+fn update_tint(clock: Res<WorldClock>, mut tint: Query<&mut BackgroundColor, With<DaylightTint>>) {
+    for mut color in &mut tint {
+        *color = BackgroundColor(clock.tint());
+    }
+}
+
+fn time_set_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut clock: ResMut<WorldClock>,
+) {
+    for command in typed(&mut commands, "time set") {
+        match command.arg::<f32>(0) {
+            Some(hour) if (0. ..24.).contains(&hour) => {
+                *clock = WorldClock::new(clock.day, hour);
+                console.print(clock.label());
+            }
+            _ => console.print("usage: time set <hour>, an hour from 0 to 24"),
+        }
+    }
+}
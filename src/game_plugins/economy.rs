@@ -0,0 +1,387 @@
+use super::{
+    data::load_data,
+    inventory::{Inventory, Item},
+    player::{InteractSet, Player, PlayerInteract},
+    world_map::{
+        chunk_gen::BiomTiles,
+        utils::tiles_to_world,
+        world_gen::RngJesus,
+    },
+    y_sort::YSort,
+};
+
+use crate::game_state::GameState;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+// how close the player has to stand to the trader to trade
+const TRADER_RANGE: f32 = 40.;
+const STARTING_MONEY: u32 = 20;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wallet>()
+            .init_resource::<ShopOpen>()
+            .insert_resource(PriceList::load())
+            .add_systems(OnExit(GameState::MENU), spawn_trader)
+            .add_systems(OnEnter(GameState::MENU), (despawn_trader, reset_wallet))
+            .add_systems(
+                Update,
+                (
+                    toggle_shop.in_set(InteractSet::Trader),
+                    close_shop_out_of_range,
+                    refresh_shop,
+                    shop_buttons,
+                )
+                    .run_if(in_state(GameState::GAME)),
+            )
+            // the shop window must not stay on top of the pause menu
+            .add_systems(OnEnter(GameState::PAUSED), close_shop);
+    }
+}
+
+/// The money the player has.
+#[derive(Resource, Debug)]
+pub struct Wallet {
+    pub money: u32,
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self {
+            money: STARTING_MONEY,
+        }
+    }
+}
+
+impl Wallet {
+    pub fn spend(&mut self, amount: u32) -> bool {
+        if self.money < amount {
+            return false;
+        }
+        self.money -= amount;
+        true
+    }
+
+    /// a wallet that is full just stays full
+    pub fn earn(&mut self, amount: u32) {
+        self.money = self.money.saturating_add(amount);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Price {
+    // what the trader wants for one item
+    pub buy: Option<u32>,
+    // what the trader pays for one item
+    pub sell: Option<u32>,
+}
+
+/// Prices of every item, loaded from `assets/data/prices.ron`.
+#[derive(Resource, Debug, Default)]
+pub struct PriceList {
+    pub prices: HashMap<Item, Price>,
+}
+
+impl PriceList {
+    fn load() -> Self {
+        match load_data::<HashMap<Item, Price>>("prices.ron") {
+            Ok(prices) => Self::new(prices),
+            Err(e) => {
+                error!("no prices loaded, the trader won't trade anything: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// sell prices above the buy price are lowered to it, so no item can be bought and
+    /// sold back for a profit
+    pub fn new(mut prices: HashMap<Item, Price>) -> Self {
+        for (item, price) in prices.iter_mut() {
+            if let (Some(buy), Some(sell)) = (price.buy, price.sell) {
+                if sell > buy {
+                    error!("{:?} sells for {} but only costs {}, lowering it", item, sell, buy);
+                    price.sell = Some(buy);
+                }
+            }
+        }
+        Self { prices }
+    }
+
+    pub fn get(&self, item: Item) -> Price {
+        self.prices.get(&item).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Component)]
+pub struct Trader;
+
+#[derive(Resource, Default)]
+pub struct ShopOpen(pub bool);
+
+#[derive(Component)]
+struct ShopRoot;
+
+#[derive(Debug, Clone, Copy)]
+enum Trade {
+    Buy,
+    Sell,
+}
+
+#[derive(Component)]
+struct ShopButton {
+    item: Item,
+    trade: Trade,
+}
+
+/// puts the trader on the first bit of land next to the world origin
+fn spawn_trader(mut commands: Commands, asset_server: Res<AssetServer>, rng_jesus: Res<RngJesus>) {
+    let mut tile = IVec2::new(2, 0);
+    'search: for radius in 2..64 {
+        for x in -radius..=radius {
+            for y in -radius..=radius {
+                let candidate = IVec2::new(x, y);
+                if rng_jesus.get_tile_type_at(&candidate).is_walkable() {
+                    tile = candidate;
+                    break 'search;
+                }
+            }
+        }
+    }
+    let (x, y) = tiles_to_world(tile);
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("images/player-v1.png"),
+            sprite: Sprite {
+                color: Color::GOLD,
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 0.5),
+            ..default()
+        },
+        Trader,
+        YSort,
+        Name::new("Trader"),
+    ));
+}
+
+fn despawn_trader(
+    mut commands: Commands,
+    mut shop_open: ResMut<ShopOpen>,
+    traders: Query<Entity, With<Trader>>,
+) {
+    shop_open.0 = false;
+    for entity in traders.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn reset_wallet(mut wallet: ResMut<Wallet>) {
+    *wallet = Wallet::default();
+}
+
+fn close_shop(
+    mut commands: Commands,
+    mut shop_open: ResMut<ShopOpen>,
+    shop_root: Query<Entity, With<ShopRoot>>,
+) {
+    shop_open.0 = false;
+    for root in shop_root.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+fn player_near_trader(player: Vec2, traders: &Query<&Transform, With<Trader>>) -> bool {
+    traders
+        .iter()
+        .any(|trader| trader.translation.truncate().distance(player) < TRADER_RANGE)
+}
+
+fn toggle_shop(
+    mut interact: ResMut<PlayerInteract>,
+    mut shop_open: ResMut<ShopOpen>,
+    traders: Query<&Transform, With<Trader>>,
+) {
+    let Some(position) = interact.position() else {
+        return;
+    };
+
+    if shop_open.0 || player_near_trader(position, &traders) {
+        shop_open.0 = !shop_open.0;
+        interact.consume();
+    }
+}
+
+fn close_shop_out_of_range(
+    mut shop_open: ResMut<ShopOpen>,
+    player: Query<&Transform, With<Player>>,
+    traders: Query<&Transform, With<Trader>>,
+) {
+    let player = player.single().translation.truncate();
+    if shop_open.0 && !player_near_trader(player, &traders) {
+        shop_open.0 = false;
+    }
+}
+
+fn text(value: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+fn trade_button(parent: &mut ChildBuilder, item: Item, trade: Trade, price: Option<u32>) {
+    let Some(price) = price else {
+        // keep the columns aligned for items that can only be bought or sold
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(90.),
+                ..default()
+            },
+            ..default()
+        });
+        return;
+    };
+
+    let label = match trade {
+        Trade::Buy => format!("Buy ${}", price),
+        Trade::Sell => format!("Sell ${}", price),
+    };
+
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(90.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::DARK_GRAY),
+                ..default()
+            },
+            ShopButton { item, trade },
+        ))
+        .with_children(|button| {
+            button.spawn(text(label, 16.));
+        });
+}
+
+/// rebuilds the shop window whenever it opens or something it shows changes
+fn refresh_shop(
+    mut commands: Commands,
+    shop_open: Res<ShopOpen>,
+    wallet: Res<Wallet>,
+    prices: Res<PriceList>,
+    inventory: Query<Ref<Inventory>, With<Player>>,
+    shop_root: Query<Entity, With<ShopRoot>>,
+) {
+    let inventory = inventory.single();
+    if !shop_open.is_changed() && !wallet.is_changed() && !inventory.is_changed() {
+        return;
+    }
+
+    for root in shop_root.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    if !shop_open.0 {
+        return;
+    }
+
+    commands
+        .spawn((
+            ShopRoot,
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK.with_a(0.8)),
+                z_index: ZIndex::Global(i32::MAX - 1),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(30.),
+                    top: Val::Percent(20.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.),
+                    padding: UiRect::all(Val::Px(12.)),
+                    ..default()
+                },
+                ..default()
+            },
+            Name::new("Shop"),
+        ))
+        .with_children(|root| {
+            root.spawn(text(format!("Trader - you have ${}", wallet.money), 20.));
+
+            for item in Item::iter() {
+                let price = prices.get(item);
+                if price.buy.is_none() && price.sell.is_none() {
+                    continue;
+                }
+
+                root.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(8.),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(text(
+                        format!("{} ({})", item.name(), inventory.count(item)),
+                        16.,
+                    ))
+                    .insert(Style {
+                        width: Val::Px(120.),
+                        ..default()
+                    });
+                    trade_button(row, item, Trade::Buy, price.buy);
+                    trade_button(row, item, Trade::Sell, price.sell);
+                });
+            }
+        });
+}
+
+fn shop_buttons(
+    mut wallet: ResMut<Wallet>,
+    prices: Res<PriceList>,
+    mut inventory: Query<&mut Inventory, With<Player>>,
+    buttons: Query<(&Interaction, &ShopButton), Changed<Interaction>>,
+) {
+    let mut inventory = inventory.single_mut();
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let price = prices.get(button.item);
+        match button.trade {
+            Trade::Buy => {
+                let Some(cost) = price.buy else { continue };
+                if wallet.spend(cost) {
+                    inventory.add(button.item, 1);
+                    debug!("bought one {} for ${}", button.item.name(), cost);
+                } else {
+                    info!("not enough money for {}", button.item.name());
+                }
+            }
+            Trade::Sell => {
+                let Some(pay) = price.sell else { continue };
+                if inventory.remove(button.item, 1) {
+                    wallet.earn(pay);
+                    debug!("sold one {} for ${}", button.item.name(), pay);
+                }
+            }
+        }
+    }
+}
+
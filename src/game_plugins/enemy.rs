@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+use bevy_turborand::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    combat::Health,
+    data::load_data,
+    daytime::{DayStarted, NightStarted},
+    economy::Wallet,
+    player::Player,
+    seasons::Season,
+    sound::{PlaySfx, Sfx},
+    world_map::{
+        chunk_gen::{BiomTiles, BiomType},
+        utils::{tile_at_world_pos, tiles_to_world},
+        world_gen::{ChunkManager, RngJesus},
+    },
+    y_sort::YSort,
+};
+use crate::{consts::CHUNK_SIZE, game_state::GameState, zoom::WorldView};
+
+const ENEMY_ATTACK_RANGE: f32 = 18.;
+// enemies further away than this from the player are removed again
+const ENEMY_DESPAWN_DISTANCE: f32 = 3000.;
+// extra distance to the edge of the screen, so nothing pops up right in view
+const SPAWN_MARGIN: f32 = 64.;
+const SPAWN_INTERVAL: f32 = 5.;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnemyDefs::load())
+            .init_resource::<EnemySpawner>()
+            .init_resource::<GlobalRng>()
+            .add_systems(
+                Update,
+                (
+                    track_night,
+                    spawn_enemies,
+                    enemy_chase,
+                    enemy_attack,
+                    enemy_death,
+                    despawn_far_enemies,
+                )
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(OnEnter(GameState::MENU), despawn_all_enemies);
+    }
+}
+
+/// One kind of hostile creature, as written in `assets/data/enemies.ron`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyDef {
+    pub name: String,
+    pub sprite: String,
+    pub color: (f32, f32, f32),
+    pub scale: f32,
+    pub health: u32,
+    pub speed: f32,
+    pub damage: u32,
+    pub attack_cooldown: f32,
+    pub sight_range: f32,
+    // money the player gets for killing one
+    pub bounty: u32,
+    pub spawn_biomes: Vec<BiomType>,
+    pub night_only: bool,
+    pub max_alive: usize,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct EnemyDefs(pub Vec<EnemyDef>);
+
+impl EnemyDefs {
+    fn load() -> Self {
+        match load_data::<Vec<EnemyDef>>("enemies.ron") {
+            Ok(defs) => Self(defs),
+            Err(e) => {
+                error!("no enemies loaded, the world will be peaceful: {}", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct EnemySpawner {
+    pub timer: Timer,
+    // enemies that are `night_only` only spawn while this is set
+    pub is_night: bool,
+}
+
+impl Default for EnemySpawner {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating),
+            is_night: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Enemy {
+    // index into `EnemyDefs`
+    pub kind: usize,
+    pub attack: Timer,
+}
+
+fn spawn_enemy(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    kind: usize,
+    def: &EnemyDef,
+    position: Vec2,
+) {
+    let (r, g, b) = def.color;
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(&def.sprite),
+            sprite: Sprite {
+                color: Color::rgb(r, g, b),
+                ..default()
+            },
+            transform: Transform {
+                translation: position.extend(0.5),
+                scale: Vec3::new(def.scale, def.scale, 1.),
+                ..default()
+            },
+            ..default()
+        },
+        Enemy {
+            kind,
+            attack: Timer::from_seconds(def.attack_cooldown, TimerMode::Once),
+        },
+        Health::new(def.health),
+        YSort,
+        Name::new(def.name.clone()),
+    ));
+}
+
+fn track_night(
+    mut spawner: ResMut<EnemySpawner>,
+    mut day_started: EventReader<DayStarted>,
+    mut night_started: EventReader<NightStarted>,
+) {
+    if day_started.read().last().is_some() {
+        spawner.is_night = false;
+    }
+    if night_started.read().last().is_some() {
+        spawner.is_night = true;
+    }
+}
+
+/// every few seconds tries to put one more enemy of each kind somewhere
+/// in the loaded world, but never where the player can see it appear
+fn spawn_enemies(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    defs: Res<EnemyDefs>,
+    rng_jesus: Res<RngJesus>,
+    chunk_manager: Res<ChunkManager>,
+    mut spawner: ResMut<EnemySpawner>,
+    mut global_rng: ResMut<GlobalRng>,
+    camera: Query<(&Transform, &OrthographicProjection), With<WorldView>>,
+    enemies: Query<&Enemy>,
+) {
+    if !spawner.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let (camera, projection) = camera.single();
+    let view_center = camera.translation.truncate();
+    let view_half_size = projection.area.half_size() + SPAWN_MARGIN;
+
+    let chunks: Vec<IVec2> = chunk_manager.spawned_chunks.keys().copied().collect();
+    if chunks.is_empty() {
+        return;
+    }
+
+    for (kind, def) in defs.0.iter().enumerate() {
+        if def.night_only && !spawner.is_night {
+            continue;
+        }
+        if enemies.iter().filter(|enemy| enemy.kind == kind).count() >= def.max_alive {
+            continue;
+        }
+
+        let chunk = chunks[global_rng.usize(0..chunks.len())];
+        if !def.spawn_biomes.contains(&rng_jesus.get_biom(&chunk)) {
+            continue;
+        }
+
+        let tile = IVec2::new(
+            chunk.x * CHUNK_SIZE.x as i32 + global_rng.i32(0..CHUNK_SIZE.x as i32),
+            chunk.y * CHUNK_SIZE.y as i32 + global_rng.i32(0..CHUNK_SIZE.y as i32),
+        );
+        if !rng_jesus.get_tile_type_at(&tile).is_walkable() {
+            continue;
+        }
+
+        let (x, y) = tiles_to_world(tile);
+        let position = Vec2::new(x, y);
+        let offset = (position - view_center).abs();
+        if offset.x < view_half_size.x && offset.y < view_half_size.y {
+            continue;
+        }
+
+        spawn_enemy(&mut commands, &asset_server, kind, def, position);
+        debug!("spawned a {} at {:?}", def.name, position);
+    }
+}
+
+/// walks towards the player once it is in sight. When the direct way is blocked
+/// by water the enemy tries to slide along the shore on just one axis.
+fn enemy_chase(
+    time: Res<Time>,
+    defs: Res<EnemyDefs>,
+    rng_jesus: Res<RngJesus>,
+    season: Res<Season>,
+    player: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    mut enemies: Query<(&mut Transform, &mut Sprite, &Enemy)>,
+) {
+    let player = player.single().translation.truncate();
+
+    for (mut transform, mut sprite, enemy) in &mut enemies {
+        let Some(def) = defs.0.get(enemy.kind) else {
+            continue;
+        };
+
+        let position = transform.translation.truncate();
+        let distance = position.distance(player);
+        if distance > def.sight_range || distance < ENEMY_ATTACK_RANGE {
+            continue;
+        }
+
+        let step = (player - position).normalize_or_zero() * def.speed * time.delta_seconds();
+        let walkable = |step: Vec2| {
+            rng_jesus
+                .get_tile_type_at(&tile_at_world_pos(position + step))
+                .is_walkable_in(*season)
+        };
+
+        let Some(step) = [step, Vec2::new(step.x, 0.), Vec2::new(0., step.y)]
+            .into_iter()
+            .find(|step| *step != Vec2::ZERO && walkable(*step))
+        else {
+            continue;
+        };
+
+        if step.x != 0. {
+            sprite.flip_x = step.x > 0.;
+        }
+        transform.translation.x += step.x;
+        transform.translation.y += step.y;
+    }
+}
+
+fn enemy_attack(
+    time: Res<Time>,
+    defs: Res<EnemyDefs>,
+    mut player: Query<(&Transform, &mut Health), (With<Player>, Without<Enemy>)>,
+    mut enemies: Query<(&Transform, &mut Enemy)>,
+) {
+    let (player, mut player_health) = player.single_mut();
+    let player = player.translation.truncate();
+
+    for (transform, mut enemy) in &mut enemies {
+        enemy.attack.tick(time.delta());
+
+        if !enemy.attack.finished()
+            || transform.translation.truncate().distance(player) > ENEMY_ATTACK_RANGE
+        {
+            continue;
+        }
+        let Some(def) = defs.0.get(enemy.kind) else {
+            continue;
+        };
+
+        player_health.damage(def.damage);
+        enemy.attack.reset();
+        debug!("a {} hit you, {} health left", def.name, player_health.current);
+    }
+}
+
+fn enemy_death(
+    mut commands: Commands,
+    defs: Res<EnemyDefs>,
+    mut wallet: ResMut<Wallet>,
+    mut sfx: EventWriter<PlaySfx>,
+    enemies: Query<(Entity, &Enemy, &Health, &Transform)>,
+) {
+    for (entity, enemy, health, transform) in enemies.iter() {
+        if !health.is_dead() {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        if let Some(def) = defs.0.get(enemy.kind) {
+            wallet.earn(def.bounty);
+            info!("killed a {}, got ${}", def.name, def.bounty);
+        }
+        sfx.send(PlaySfx::at(Sfx::EnemyKilled, transform.translation.truncate()));
+    }
+}
+
+fn despawn_all_enemies(mut commands: Commands, enemies: Query<Entity, With<Enemy>>) {
+    for entity in enemies.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn despawn_far_enemies(
+    mut commands: Commands,
+    player: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    let player = player.single().translation.truncate();
+
+    for (entity, transform) in enemies.iter() {
+        if transform.translation.truncate().distance(player) > ENEMY_DESPAWN_DISTANCE {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, EnumIter, Serialize, Deserialize)]
+pub enum Item {
+    Wood,
+    Pig,
+    Berries,
+    Axe,
+}
+
+impl Item {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::Wood => "Wood",
+            Item::Pig => "Pig",
+            Item::Berries => "Berries",
+            Item::Axe => "Axe",
+        }
+    }
+
+    /// the item called `name`, whatever the case
+    pub fn from_name(name: &str) -> Option<Item> {
+        Item::iter().find(|item| item.name().eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Component, Debug, Default, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Inventory {
+    pub items: HashMap<Item, u32>,
+}
+
+impl Inventory {
+    pub fn count(&self, item: Item) -> u32 {
+        self.items.get(&item).copied().unwrap_or(0)
+    }
+
+    pub fn has(&self, item: Item) -> bool {
+        self.count(item) > 0
+    }
+
+    pub fn add(&mut self, item: Item, amount: u32) {
+        *self.items.entry(item).or_default() += amount;
+    }
+
+    /// takes `amount` items out, returns false and leaves the inventory alone if there are not enough
+    pub fn remove(&mut self, item: Item, amount: u32) -> bool {
+        let count = self.count(item);
+        if count < amount {
+            return false;
+        }
+
+        if count == amount {
+            self.items.remove(&item);
+        } else {
+            self.items.insert(item, count - amount);
+        }
+        true
+    }
+}
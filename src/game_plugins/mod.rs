@@ -1,6 +1,22 @@
+pub mod actions;
+pub mod animation;
+pub mod combat;
+pub mod console;
+pub mod data;
+pub mod daytime;
+pub mod economy;
+pub mod enemy;
+pub mod inventory;
+pub mod movement;
 pub mod tree;
+pub mod weather;
 pub mod player;
+pub mod pig;
+pub mod save;
+pub mod seasons;
+pub mod sound;
 pub mod world_map;
+pub mod y_sort;
 
 // use player::Player;
 // use tree::TreePlugin;
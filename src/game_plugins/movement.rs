@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use super::{seasons::Season, world_map::chunk_gen::TileType};
+use crate::game_state::GameState;
+
+/// Moves everything with a `Velocity` in `FixedUpdate`, so the speed doesn't depend
+/// on the frame rate. Whatever decides where something wants to go runs before `MovementSet`.
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Velocity>().add_systems(
+            FixedUpdate,
+            apply_velocity
+                .in_set(MovementSet)
+                .run_if(in_state(GameState::GAME)),
+        );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MovementSet;
+
+/// World units per second.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct Velocity(pub Vec2);
+
+/// How the ground under something changes the way it moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Traction {
+    // the top speed is multiplied by this
+    pub speed: f32,
+    // units per second squared while speeding up
+    pub acceleration: f32,
+    // units per second squared while slowing down
+    pub friction: f32,
+}
+
+impl Traction {
+    pub fn of(tile: TileType, season: Season) -> Traction {
+        let (speed, acceleration, friction) = match tile {
+            TileType::GrassLand => (1.0, 1200., 1600.),
+            TileType::Beach => (0.8, 900., 1400.),
+            // snow
+            TileType::White => (0.7, 600., 800.),
+            // frozen over, fast but slippery
+            TileType::ShallowWater if season == Season::Winter => (1.0, 250., 100.),
+            // wading
+            TileType::ShallowWater => (0.5, 600., 1600.),
+            // swimming
+            TileType::DeepWater => (0.4, 300., 500.),
+        };
+
+        Traction {
+            speed,
+            acceleration,
+            friction,
+        }
+    }
+}
+
+/// moves `velocity` towards `target`, speeding up with the acceleration of the ground
+/// and slowing down (or turning) with its friction
+pub fn steer(velocity: Vec2, target: Vec2, traction: Traction, delta_seconds: f32) -> Vec2 {
+    let speeding_up = target != Vec2::ZERO && target.dot(velocity) >= velocity.length_squared();
+    let rate = if speeding_up {
+        traction.acceleration
+    } else {
+        traction.friction
+    };
+
+    velocity + (target - velocity).clamp_length_max(rate * delta_seconds)
+}
+
+fn apply_velocity(time: Res<Time>, mut moving: Query<(&mut Transform, &Velocity)>) {
+    for (mut transform, velocity) in &mut moving {
+        transform.translation += (velocity.0 * time.delta_seconds()).extend(0.);
+    }
+}
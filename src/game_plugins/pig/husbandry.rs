@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{spawn_pig, Pig, PigBehaviour};
+use crate::game_plugins::seasons::Season;
+
+// how long a pig stays fed after eating some berries
+const FED_SECONDS: f32 = 60.;
+// how long a piglet takes to become an adult
+const PIGLET_GROWTH_SECONDS: f32 = 180.;
+const BREED_RANGE: f32 = 40.;
+const BREED_SECONDS: f32 = 5.;
+const BREED_COOLDOWN_SECONDS: f32 = 120.;
+
+/// Two pigs that found each other. Both of them carry it, pointing at the other one.
+#[derive(Component)]
+pub struct Breeding {
+    pub partner: Entity,
+    pub timer: Timer,
+}
+
+pub fn fed_timer(elapsed: f32) -> Timer {
+    let mut timer = Timer::from_seconds(FED_SECONDS, TimerMode::Once);
+    timer.set_elapsed(Duration::from_secs_f32(elapsed));
+    timer
+}
+
+pub fn piglet_growth_timer(elapsed: f32) -> Timer {
+    let mut timer = Timer::from_seconds(PIGLET_GROWTH_SECONDS, TimerMode::Once);
+    timer.set_elapsed(Duration::from_secs_f32(elapsed));
+    timer
+}
+
+pub fn breed_cooldown_timer(elapsed: f32) -> Timer {
+    let mut timer = Timer::from_seconds(BREED_COOLDOWN_SECONDS, TimerMode::Once);
+    timer.set_elapsed(Duration::from_secs_f32(elapsed));
+    timer
+}
+
+pub fn feed(pig: &mut Pig) {
+    pig.fed = Some(fed_timer(0.));
+    debug!("fed a pig");
+}
+
+/// fed pigs get hungry again after a while
+pub fn hunger(time: Res<Time>, season: Res<Season>, mut pigs: Query<&mut Pig>) {
+    for mut pig in &mut pigs {
+        pig.breed_cooldown
+            .tick(time.delta().mul_f32(season.breeding_factor()));
+
+        let Some(fed) = pig.fed.as_mut() else {
+            continue;
+        };
+        if fed.tick(time.delta()).finished() {
+            pig.fed = None;
+        }
+    }
+}
+
+fn ready_to_breed(pig: &Pig, season: Season) -> bool {
+    !pig.is_piglet()
+        && pig.fed.is_some()
+        && pig.breed_cooldown.finished()
+        && season.breeding_factor() > 0.
+}
+
+/// pairs up fed adults standing close to each other and lets them have a piglet
+pub fn breed_pigs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    season: Res<Season>,
+    mut pigs: Query<(Entity, &Transform, &mut Pig, Option<&mut Breeding>)>,
+) {
+    // first tick the couples that already found each other
+    let alive: Vec<Entity> = pigs.iter().map(|(entity, _, _, _)| entity).collect();
+    let mut finished: Vec<(Entity, Entity)> = Vec::new();
+    let mut lonely: Vec<Entity> = Vec::new();
+    for (entity, _, _, breeding) in pigs.iter_mut() {
+        let Some(mut breeding) = breeding else {
+            continue;
+        };
+        // the partner got caught or its chunk unloaded
+        if !alive.contains(&breeding.partner) {
+            lonely.push(entity);
+            continue;
+        }
+        if breeding.timer.tick(time.delta()).just_finished() {
+            finished.push((entity, breeding.partner));
+        }
+    }
+
+    for entity in lonely {
+        commands.entity(entity).remove::<Breeding>();
+    }
+
+    for (entity, partner) in finished {
+        // both partners finish in the same frame, only one of them gets to spawn the piglet
+        let spawn_piglet = entity < partner;
+        let Ok([(_, a, mut pig_a, _), (_, b, mut pig_b, _)]) = pigs.get_many_mut([entity, partner])
+        else {
+            continue;
+        };
+
+        for pig in [&mut pig_a, &mut pig_b] {
+            pig.fed = None;
+            pig.breed_cooldown = breed_cooldown_timer(0.);
+        }
+        commands.entity(entity).remove::<Breeding>();
+
+        if spawn_piglet {
+            let position = (a.translation.truncate() + b.translation.truncate()) / 2.;
+            let piglet = Pig {
+                growth: Some(piglet_growth_timer(0.)),
+                ..default()
+            };
+            spawn_pig(&mut commands, &asset_server, position, piglet);
+            info!("a piglet was born at {:?}", position);
+        }
+    }
+
+    // then look for new couples
+    let candidates: Vec<(Entity, Vec2)> = pigs
+        .iter()
+        .filter(|(_, _, pig, breeding)| breeding.is_none() && ready_to_breed(pig, *season))
+        .map(|(entity, transform, _, _)| (entity, transform.translation.truncate()))
+        .collect();
+
+    let mut taken: Vec<Entity> = Vec::new();
+    for (i, (entity, position)) in candidates.iter().enumerate() {
+        if taken.contains(entity) {
+            continue;
+        }
+
+        let partner = candidates[i + 1..].iter().find(|(other, other_position)| {
+            !taken.contains(other) && position.distance(*other_position) < BREED_RANGE
+        });
+        let Some((partner, _)) = partner else {
+            continue;
+        };
+
+        taken.push(*entity);
+        taken.push(*partner);
+        for (pig, other) in [(*entity, *partner), (*partner, *entity)] {
+            commands.entity(pig).insert(Breeding {
+                partner: other,
+                timer: Timer::from_seconds(BREED_SECONDS, TimerMode::Once),
+            });
+            if let Ok((_, _, mut pig, _)) = pigs.get_mut(pig) {
+                pig.behaviour = PigBehaviour::Idle;
+            }
+        }
+    }
+}
+
+pub fn grow_piglets(time: Res<Time>, mut pigs: Query<(&mut Pig, &mut Transform)>) {
+    for (mut pig, mut transform) in &mut pigs {
+        let Some(growth) = pig.growth.as_mut() else {
+            continue;
+        };
+        if !growth.tick(time.delta()).finished() {
+            continue;
+        }
+
+        pig.growth = None;
+        transform.scale = Vec3::new(pig.scale(), pig.scale(), 1.);
+        debug!("a piglet grew up");
+    }
+}
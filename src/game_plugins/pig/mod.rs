@@ -0,0 +1,427 @@
+pub mod husbandry;
+
+use super::{
+    console::{typed, Console, ConsoleApp, ConsoleCommand},
+    inventory::{Inventory, Item},
+    player::{InteractSet, Player, PlayerInteract},
+    seasons::Season,
+    sound::{PlaySfx, Sfx},
+    world_map::{
+        chunk_gen::{BiomTiles, BiomType},
+        utils::{tile_at_world_pos, tiles_to_chunks, tiles_to_world},
+        world_gen::{ChunkDespawned, ChunkManager, ChunkSet, ChunkSpawned, RngJesus},
+    },
+    y_sort::YSort,
+};
+use crate::{consts::CHUNK_SIZE, game_state::GameState};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_turborand::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use husbandry::{breed_pigs, grow_piglets, hunger, Breeding};
+
+const PIGS_PER_CHUNK: usize = 3;
+// keeps the pigs of a chunk from being placed like its trees
+const PIG_SEED_SALT: u64 = 0x9166;
+const PIG_WANDER_SPEED: f32 = 30.;
+const PIG_FLEE_SPEED: f32 = 90.;
+// the player scares pigs when getting closer than this
+const PIG_FLEE_RADIUS: f32 = 60.;
+const PIG_INTERACT_RANGE: f32 = 20.;
+
+pub struct PigPlugin;
+
+impl Plugin for PigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PigCount>()
+            .init_resource::<PigChunkStore>()
+            .init_resource::<GlobalRng>()
+            .add_systems(
+                Update,
+                (
+                    (spawn_chunk_pigs, store_chunk_pigs).in_set(ChunkSet::Content),
+                    pig_brain,
+                    pig_movement,
+                    pig_oink,
+                    interact_pig.in_set(InteractSet::Pig),
+                    hunger,
+                    breed_pigs,
+                    grow_piglets,
+                    count_pigs,
+                )
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(OnEnter(GameState::MENU), despawn_all_pigs)
+            .add_console_command(
+                "spawn pig",
+                "spawn pig <n> - puts pigs next to the player",
+                spawn_pig_command,
+            )
+            .register_type::<Pig>();
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PigCount(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub enum PigBehaviour {
+    Idle,
+    Wander { target: Vec2 },
+    Flee,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Pig {
+    pub behaviour: PigBehaviour,
+    // time until the pig thinks about what to do next
+    pub think: Timer,
+    pub oink: Timer,
+    // ticking while the pig is still a piglet
+    pub growth: Option<Timer>,
+    // ticking while the pig is fed, a fed adult is ready to breed
+    pub fed: Option<Timer>,
+    pub breed_cooldown: Timer,
+}
+
+impl Default for Pig {
+    fn default() -> Self {
+        Self {
+            behaviour: PigBehaviour::Idle,
+            think: Timer::from_seconds(2., TimerMode::Once),
+            oink: Timer::from_seconds(8., TimerMode::Once),
+            growth: None,
+            fed: None,
+            breed_cooldown: Timer::from_seconds(0., TimerMode::Once),
+        }
+    }
+}
+
+impl Pig {
+    pub fn is_piglet(&self) -> bool {
+        self.growth.is_some()
+    }
+
+    pub fn scale(&self) -> f32 {
+        if self.is_piglet() {
+            0.6
+        } else {
+            1.
+        }
+    }
+
+    pub fn to_state(&self, position: Vec2) -> PigState {
+        PigState {
+            position,
+            growth_elapsed: self.growth.as_ref().map(|growth| growth.elapsed_secs()),
+            fed_elapsed: self.fed.as_ref().map(|fed| fed.elapsed_secs()),
+            breed_cooldown_elapsed: (!self.breed_cooldown.finished())
+                .then(|| self.breed_cooldown.elapsed_secs()),
+        }
+    }
+}
+
+/// What is left of a pig once its chunk is not spawned anymore.
+#[derive(Debug, Clone)]
+pub struct PigState {
+    pub position: Vec2,
+    pub growth_elapsed: Option<f32>,
+    pub fed_elapsed: Option<f32>,
+    // only while the pig has bred not long ago
+    pub breed_cooldown_elapsed: Option<f32>,
+}
+
+impl PigState {
+    fn to_pig(&self) -> Pig {
+        let mut pig = Pig::default();
+        if let Some(elapsed) = self.growth_elapsed {
+            pig.growth = Some(husbandry::piglet_growth_timer(elapsed));
+        }
+        if let Some(elapsed) = self.fed_elapsed {
+            pig.fed = Some(husbandry::fed_timer(elapsed));
+        }
+        if let Some(elapsed) = self.breed_cooldown_elapsed {
+            pig.breed_cooldown = husbandry::breed_cooldown_timer(elapsed);
+        }
+        pig
+    }
+}
+
+/// Pigs of every chunk that was visited but is not spawned right now.
+#[derive(Resource, Debug, Default)]
+pub struct PigChunkStore {
+    pub chunks: HashMap<IVec2, Vec<PigState>>,
+}
+
+pub fn spawn_pig(commands: &mut Commands, asset_server: &AssetServer, position: Vec2, pig: Pig) {
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("images/pig.png"),
+            transform: Transform {
+                translation: position.extend(0.5),
+                scale: Vec3::new(pig.scale(), pig.scale(), 1.),
+                ..default()
+            },
+            ..default()
+        },
+        pig,
+        YSort,
+        Name::new("Pig"),
+    ));
+}
+
+fn spawn_pig_command(
+    mut commands: Commands,
+    mut typed_commands: EventReader<ConsoleCommand>,
+    asset_server: Res<AssetServer>,
+    mut console: ResMut<Console>,
+    player: Query<&Transform, With<Player>>,
+) {
+    for command in typed(&mut typed_commands, "spawn pig") {
+        let count = command.arg::<u32>(0).unwrap_or(1);
+        let position = player.single().translation.truncate();
+        for i in 0..count {
+            // in a row to the right of the player
+            let offset = Vec2::new(24. * (i + 1) as f32, 0.);
+            spawn_pig(&mut commands, &asset_server, position + offset, Pig::default());
+        }
+        console.print(format!("spawned {} pigs", count));
+    }
+}
+
+/// picks the spots for the pigs of a grassland chunk that was never visited before.
+fn generate_chunk_pigs(rng_jesus: &RngJesus, chunk_position: IVec2) -> Vec<PigState> {
+    if rng_jesus.get_biom(&chunk_position) != BiomType::GrassLand {
+        return Vec::new();
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(rng_jesus.chunk_seed(chunk_position, PIG_SEED_SALT));
+
+    let mut pigs = Vec::new();
+    for _ in 0..rng.gen_range(0..=PIGS_PER_CHUNK) {
+        let tile = IVec2::new(
+            chunk_position.x * CHUNK_SIZE.x as i32 + rng.gen_range(0..CHUNK_SIZE.x as i32),
+            chunk_position.y * CHUNK_SIZE.y as i32 + rng.gen_range(0..CHUNK_SIZE.y as i32),
+        );
+        if !rng_jesus.get_tile_type_at(&tile).is_walkable() {
+            continue;
+        }
+        let (x, y) = tiles_to_world(tile);
+        pigs.push(PigState {
+            position: Vec2::new(x, y),
+            growth_elapsed: None,
+            fed_elapsed: None,
+            breed_cooldown_elapsed: None,
+        });
+    }
+
+    pigs
+}
+
+fn spawn_chunk_pigs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rng_jesus: Res<RngJesus>,
+    mut store: ResMut<PigChunkStore>,
+    mut chunk_spawned: EventReader<ChunkSpawned>,
+) {
+    for event in chunk_spawned.read() {
+        let pigs = match store.chunks.remove(&event.position) {
+            Some(pigs) => pigs,
+            None => generate_chunk_pigs(&rng_jesus, event.position),
+        };
+
+        for pig in pigs.iter() {
+            spawn_pig(&mut commands, &asset_server, pig.position, pig.to_pig());
+        }
+    }
+}
+
+fn store_chunk_pigs(
+    mut commands: Commands,
+    mut store: ResMut<PigChunkStore>,
+    mut chunk_despawned: EventReader<ChunkDespawned>,
+    pigs: Query<(Entity, &Transform, &Pig)>,
+) {
+    for event in chunk_despawned.read() {
+        let mut chunk_pigs = Vec::new();
+
+        for (entity, transform, pig) in pigs.iter() {
+            let position = transform.translation.truncate();
+            if tiles_to_chunks(tile_at_world_pos(position)) == event.position {
+                chunk_pigs.push(pig.to_state(position));
+                commands.entity(entity).despawn();
+            }
+        }
+
+        store.chunks.insert(event.position, chunk_pigs);
+    }
+}
+
+/// the little state machine deciding if a pig idles, wanders around or runs away
+fn pig_brain(
+    time: Res<Time>,
+    mut global_rng: ResMut<GlobalRng>,
+    player: Query<&Transform, (With<Player>, Without<Pig>)>,
+    mut pigs: Query<(&Transform, &mut Pig), Without<Breeding>>,
+) {
+    let player = player.single().translation.truncate();
+
+    for (transform, mut pig) in &mut pigs {
+        let position = transform.translation.truncate();
+
+        // keeps running for a bit after the player is out of sight
+        if position.distance(player) < PIG_FLEE_RADIUS {
+            pig.behaviour = PigBehaviour::Flee;
+            pig.think = Timer::from_seconds(1.5, TimerMode::Once);
+            continue;
+        }
+
+        pig.think.tick(time.delta());
+        if !pig.think.finished() {
+            continue;
+        }
+
+        pig.behaviour = if global_rng.bool() {
+            PigBehaviour::Idle
+        } else {
+            let offset = Vec2::new(
+                global_rng.f32_normalized() * 100.,
+                global_rng.f32_normalized() * 100.,
+            );
+            PigBehaviour::Wander {
+                target: position + offset,
+            }
+        };
+
+        let duration = 1. + global_rng.f32() * 4.;
+        pig.think = Timer::from_seconds(duration, TimerMode::Once);
+    }
+}
+
+fn pig_movement(
+    time: Res<Time>,
+    rng_jesus: Res<RngJesus>,
+    season: Res<Season>,
+    chunk_manager: Res<ChunkManager>,
+    player: Query<&Transform, (With<Player>, Without<Pig>)>,
+    mut pigs: Query<(&mut Transform, &mut Sprite, &mut Pig), Without<Breeding>>,
+) {
+    let player = player.single().translation.truncate();
+
+    for (mut transform, mut sprite, mut pig) in &mut pigs {
+        let position = transform.translation.truncate();
+
+        let (direction, speed) = match pig.behaviour {
+            PigBehaviour::Idle => continue,
+            PigBehaviour::Wander { target } => {
+                if position.distance(target) < 2. {
+                    pig.behaviour = PigBehaviour::Idle;
+                    continue;
+                }
+                (target - position, PIG_WANDER_SPEED)
+            }
+            PigBehaviour::Flee => (position - player, PIG_FLEE_SPEED),
+        };
+
+        let step = direction.normalize_or_zero() * speed * time.delta_seconds();
+        let next = position + step;
+
+        // pigs can't swim, so they just stop at the shore and think again. They also stay
+        // on spawned chunks, a pig out there would never be stored with its chunk.
+        let tile = tile_at_world_pos(next);
+        if !rng_jesus.get_tile_type_at(&tile).is_walkable_in(*season)
+            || !chunk_manager.contains(&tiles_to_chunks(tile))
+        {
+            pig.behaviour = PigBehaviour::Idle;
+            continue;
+        }
+
+        if step.x != 0. {
+            sprite.flip_x = step.x > 0.;
+        }
+        transform.translation.x = next.x;
+        transform.translation.y = next.y;
+    }
+}
+
+fn pig_oink(
+    time: Res<Time>,
+    mut global_rng: ResMut<GlobalRng>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut pigs: Query<(&Transform, &mut Pig)>,
+) {
+    for (transform, mut pig) in &mut pigs {
+        pig.oink.tick(time.delta());
+        if !pig.oink.finished() {
+            continue;
+        }
+        pig.oink = Timer::from_seconds(6. + global_rng.f32() * 20., TimerMode::Once);
+
+        sfx.send(PlaySfx::at(Sfx::Oink, transform.translation.truncate()));
+    }
+}
+
+/// feeds the closest hungry pig in reach if there are berries to give, otherwise
+/// grabs the closest pig and puts it into the inventory, ready to be sold.
+/// Pigs that are busy breeding are left alone.
+fn interact_pig(
+    mut commands: Commands,
+    mut interact: ResMut<PlayerInteract>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut player: Query<&mut Inventory, With<Player>>,
+    mut pigs: Query<(Entity, &Transform, &mut Pig), Without<Breeding>>,
+) {
+    let Some(position) = interact.position() else {
+        return;
+    };
+
+    let mut inventory = player.single_mut();
+    let has_berries = inventory.has(Item::Berries);
+
+    let mut in_reach: Vec<_> = pigs
+        .iter_mut()
+        .map(|(entity, transform, pig)| {
+            let distance = transform.translation.truncate().distance(position);
+            (entity, pig, distance)
+        })
+        .filter(|(_, _, distance)| *distance < PIG_INTERACT_RANGE)
+        .collect();
+    in_reach.sort_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+    // a fed pig is only grabbed when there is no hungry one to feed instead
+    let hungry = in_reach
+        .iter()
+        .position(|(_, pig, _)| has_berries && pig.fed.is_none());
+    let Some((entity, mut pig, _)) = in_reach.into_iter().nth(hungry.unwrap_or(0)) else {
+        return;
+    };
+    interact.consume();
+
+    if hungry.is_some() && inventory.remove(Item::Berries, 1) {
+        husbandry::feed(&mut pig);
+        return;
+    }
+
+    commands.entity(entity).despawn();
+    inventory.add(Item::Pig, 1);
+    sfx.send(PlaySfx::at(Sfx::PigGrabbed, position));
+}
+
+fn despawn_all_pigs(
+    mut commands: Commands,
+    mut store: ResMut<PigChunkStore>,
+    pigs: Query<Entity, With<Pig>>,
+) {
+    store.chunks.clear();
+    for entity in pigs.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn count_pigs(mut pig_count: ResMut<PigCount>, pigs: Query<&Pig>) {
+    pig_count.0 = pigs.iter().count();
+}
@@ -1,13 +1,67 @@
 use bevy::prelude::*;
 
-use crate::zoom::WorldView;
+use crate::game_state::GameState;
+
+use super::{
+    actions::{Action, ActionState},
+    animation::{SpriteAnimation, SpriteSheets},
+    combat::{Health, PLAYER_MAX_HEALTH},
+    console::{typed, Console, ConsoleApp, ConsoleCommand},
+    inventory::{Inventory, Item},
+    movement::{steer, MovementSet, Traction, Velocity},
+    seasons::Season,
+    world_map::{
+        chunk_gen::BiomTiles,
+        utils::{tile_at_world_pos, tiles_to_world},
+        world_gen::RngJesus,
+    },
+    y_sort::YSort,
+};
+
+// how much faster the player flies in noclip mode
+const NOCLIP_SPEED: f32 = 16.;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, character_movement);
+        app.add_event::<PlayerTeleported>()
+            .init_resource::<PlayerInteract>()
+            .init_resource::<Noclip>()
+            .configure_sets(
+                Update,
+                (
+                    InteractSet::Trader,
+                    InteractSet::Enemy,
+                    InteractSet::Pig,
+                    InteractSet::Tree,
+                )
+                    .chain()
+                    .after(player_interact),
+            )
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (toggle_noclip, player_interact).run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(
+                FixedUpdate,
+                character_movement
+                    .before(MovementSet)
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(OnEnter(GameState::MENU), reset_player)
+            .add_console_command("tp", "tp <x> <y> - teleports the player onto a tile", tp_command)
+            .add_console_command(
+                "noclip",
+                "noclip - flies fast over any ground, again to walk",
+                noclip_command,
+            )
+            .add_console_command(
+                "give",
+                "give <item> <n> - puts items into the inventory",
+                give_command,
+            );
     }
 }
 
@@ -22,76 +76,175 @@ impl Default for Player {
     }
 }
 
-pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let texture = asset_server.load("images/player-v1.png"); //character.png");
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: Vec3 {
-                    z: 1.0,
-                    ..default()
-                },
-                ..Default::default()
-            },
-            texture,
+/// Sent whenever the player is put somewhere instead of walking there, like a `tp`,
+/// a respawn or a loaded save. The camera jumps along instead of gliding over.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerTeleported;
+
+/// Debug fly mode: the player goes straight where the keys point, much faster and
+/// without caring about the ground.
+#[derive(Resource, Debug, Default)]
+pub struct Noclip(pub bool);
+
+/// The interact key press of this frame. Everything the player can chop, catch, talk to
+/// or hit looks at this instead of reading the key on its own, in `InteractSet` order.
+/// The first one with something in reach consumes it, so one press only does one thing.
+#[derive(Resource, Debug, Default)]
+pub struct PlayerInteract(Option<Vec2>);
+
+impl PlayerInteract {
+    /// where the player stood when pressing interact, `None` if it wasn't pressed or is used up
+    pub fn position(&self) -> Option<Vec2> {
+        self.0
+    }
+
+    pub fn consume(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// Who gets the interact press first, talking to the trader wins over fighting,
+/// fighting over catching pigs and all of them over chopping trees.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InteractSet {
+    Trader,
+    Enemy,
+    Pig,
+    Tree,
+}
+
+fn player_interact(
+    actions: Res<ActionState>,
+    player: Query<&Transform, With<Player>>,
+    mut interact: ResMut<PlayerInteract>,
+) {
+    interact.0 = None;
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+
+    interact.0 = Some(player.single().translation.truncate());
+}
+
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>, sheets: Res<SpriteSheets>) {
+    let transform = Transform::from_xyz(0., 0., 1.);
+    let mut player = match sheets.bundle("player", transform) {
+        Some(sheet) => commands.spawn(sheet),
+        // without the sheet the old picture is better than nothing
+        None => commands.spawn(SpriteBundle {
+            transform,
+            texture: asset_server.load("images/player-v1.png"),
             ..default()
-        },
+        }),
+    };
+
+    player.insert((
+        SpriteAnimation::new("player"),
         Player::default(),
+        Velocity::default(),
+        YSort,
+        Inventory::default(),
+        Health::new(PLAYER_MAX_HEALTH),
         Name::new("Player"),
     ));
 }
 
+/// a fresh world starts with a fresh player standing at the origin
+fn reset_player(
+    mut noclip: ResMut<Noclip>,
+    mut player: Query<(&mut Transform, &mut Velocity, &mut Health, &mut Inventory), With<Player>>,
+) {
+    let (mut transform, mut velocity, mut health, mut inventory) = player.single_mut();
+    transform.translation.x = 0.;
+    transform.translation.y = 0.;
+    *velocity = Velocity::default();
+    noclip.0 = false;
+    *health = Health::new(PLAYER_MAX_HEALTH);
+    *inventory = Inventory::default();
+}
+
+/// speeds the player up towards where the keys or the stick point, the ground decides how
+/// quickly. `MovementSet` does the actual moving, the camera follows on its own (see `zoom::follow_player`).
 pub fn character_movement(
-    mut player: Query<(&mut Transform, &Player), (With<Player>, Without<WorldView>)>,
-    mut world_view: Query<&mut Transform, (With<WorldView>, Without<Player>)>,
-    input: Res<Input<KeyCode>>,
+    mut player: Query<(&Transform, &mut Velocity, &Player)>,
+    actions: Res<ActionState>,
+    noclip: Res<Noclip>,
+    season: Res<Season>,
+    rng_jesus: Res<RngJesus>,
     time: Res<Time>,
 ) {
-    let (mut tansform_player, player) = player.single_mut();
-    let mut tansform_world_view = world_view.single_mut();
-
-    // for wv in &mut world_view {
-    //     info!("{:?}", wv);
-    // }
-    // for (mut transform, player) in &mut player.into {
-    let movement_amount = player.speed * time.delta_seconds();
-
-    let mut move_x: f32 = 0.;
-    let mut move_y: f32 = 0.;
+    let (transform, mut velocity, player) = player.single_mut();
 
-    if input.pressed(KeyCode::W) {
-        move_y += movement_amount;
-    }
-    if input.pressed(KeyCode::S) {
-        move_y -= movement_amount;
-    }
-    if input.pressed(KeyCode::D) {
-        move_x += movement_amount;
-    }
-    if input.pressed(KeyCode::A) {
-        move_x -= movement_amount;
+    // keys and the stick both give a direction no longer than 1, so diagonals are as fast as straight lines
+    let direction = actions.movement();
+    if noclip.0 {
+        velocity.0 = direction * player.speed * NOCLIP_SPEED;
+        return;
     }
 
-    if move_x != 0. && move_y != 0. {
-        move_x /= 2.;
-        move_y /= 2.;
+    let tile = tile_at_world_pos(transform.translation.truncate());
+    let traction = Traction::of(rng_jesus.get_tile_type_at(&tile), *season);
+    let mut target = direction * player.speed * traction.speed;
+    if actions.pressed(Action::Sprint) {
+        target *= 2.;
     }
 
-    if input.pressed(KeyCode::ShiftLeft) || input.pressed(KeyCode::ShiftRight) {
-        move_x *= 2.;
-        move_y *= 2.;
+    velocity.0 = steer(velocity.0, target, traction, time.delta_seconds());
+}
+
+fn toggle_noclip(actions: Res<ActionState>, mut noclip: ResMut<Noclip>) {
+    if actions.just_pressed(Action::ToggleNoclip) {
+        noclip.0 = !noclip.0;
+        info!("noclip {}", if noclip.0 { "on" } else { "off" });
     }
-    if input.pressed(KeyCode::ControlLeft) || input.pressed(KeyCode::ControlRight) {
-        move_x *= 16.;
-        move_y *= 16.;
+}
+
+fn noclip_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut noclip: ResMut<Noclip>,
+) {
+    for _ in typed(&mut commands, "noclip") {
+        noclip.0 = !noclip.0;
+        console.print(if noclip.0 { "noclip on" } else { "noclip off" });
     }
-    if input.pressed(KeyCode::ControlLeft) && input.pressed(KeyCode::ControlRight) {
-        move_x *= 4.;
-        move_y *= 4.;
+}
+
+fn tp_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut teleported: EventWriter<PlayerTeleported>,
+    mut player: Query<(&mut Transform, &mut Velocity), With<Player>>,
+) {
+    for command in typed(&mut commands, "tp") {
+        let (Some(x), Some(y)) = (command.arg::<i32>(0), command.arg::<i32>(1)) else {
+            console.print("usage: tp <x> <y>");
+            continue;
+        };
+
+        let (world_x, world_y) = tiles_to_world(IVec2::new(x, y));
+        let (mut transform, mut velocity) = player.single_mut();
+        transform.translation.x = world_x;
+        transform.translation.y = world_y;
+        *velocity = Velocity::default();
+        teleported.send(PlayerTeleported);
+        console.print(format!("teleported to tile {} {}", x, y));
     }
+}
+
+fn give_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut player: Query<&mut Inventory, With<Player>>,
+) {
+    for command in typed(&mut commands, "give") {
+        let Some(item) = command.args.first().and_then(|name| Item::from_name(name)) else {
+            console.print("usage: give <item> <n>, items are wood, pig, berries and axe");
+            continue;
+        };
+        let count = command.arg::<u32>(1).unwrap_or(1);
 
-    tansform_player.translation.y += move_y;
-    tansform_player.translation.x += move_x;
-    tansform_world_view.translation.y += move_y;
-    tansform_world_view.translation.x += move_x;
+        player.single_mut().add(item, count);
+        console.print(format!("gave {} {}", count, item.name()));
+    }
 }
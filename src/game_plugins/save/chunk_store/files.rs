@@ -0,0 +1,88 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use bevy::math::IVec2;
+
+use super::{ChunkRecord, ChunkStore};
+
+const CHUNK_EXTENSION: &str = "chunk";
+
+/// Every chunk of a world in a file of its own, `<x>_<y>.chunk` in one folder.
+/// Each file holds `ChunkRecord::to_bytes`.
+pub struct FileChunkStore {
+    folder: PathBuf,
+}
+
+impl FileChunkStore {
+    pub fn open(folder: &Path) -> Result<Self, String> {
+        fs::create_dir_all(folder).map_err(|e| format!("can't create {}: {}", folder.display(), e))?;
+
+        Ok(Self {
+            folder: folder.to_path_buf(),
+        })
+    }
+
+    fn chunk_path(&self, position: IVec2) -> PathBuf {
+        self.folder
+            .join(format!("{}_{}.{}", position.x, position.y, CHUNK_EXTENSION))
+    }
+}
+
+fn position_from_path(path: &Path) -> Option<IVec2> {
+    if path.extension()? != CHUNK_EXTENSION {
+        return None;
+    }
+    let (x, y) = path.file_stem()?.to_str()?.split_once('_')?;
+
+    Some(IVec2::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+impl ChunkStore for FileChunkStore {
+    fn read(&mut self, position: IVec2) -> Result<Option<ChunkRecord>, String> {
+        let path = self.chunk_path(position);
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("can't read {}: {}", path.display(), e)),
+        };
+
+        ChunkRecord::from_bytes(position, &content).map(Some)
+    }
+
+    fn write_batch(&mut self, records: &[ChunkRecord]) -> Result<(), String> {
+        // everything goes into temporary files first, they only replace the real ones
+        // once all of them were written
+        let mut written = Vec::new();
+        for record in records {
+            let path = self.chunk_path(record.position);
+            let tmp_path = path.with_extension("tmp");
+            if let Err(e) = fs::write(&tmp_path, record.to_bytes()) {
+                for (tmp_path, _) in written {
+                    let _ = fs::remove_file(tmp_path);
+                }
+                return Err(format!("can't write {}: {}", tmp_path.display(), e));
+            }
+            written.push((tmp_path, path));
+        }
+
+        for (tmp_path, path) in written {
+            fs::rename(&tmp_path, &path)
+                .map_err(|e| format!("can't write {}: {}", path.display(), e))?;
+        }
+
+        Ok(())
+    }
+
+    fn positions(&mut self) -> Result<Vec<IVec2>, String> {
+        let entries = fs::read_dir(&self.folder)
+            .map_err(|e| format!("can't read {}: {}", self.folder.display(), e))?;
+
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| position_from_path(&entry.path()))
+            .collect())
+    }
+}
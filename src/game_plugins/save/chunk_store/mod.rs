@@ -0,0 +1,178 @@
+pub mod files;
+pub mod region;
+pub mod sqlite;
+pub mod writer;
+
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use super::{
+    format::{slot_path, ChunkEntities},
+    migrations::{self, CHUNK_MIGRATIONS},
+};
+use crate::{consts::CHUNK_SIZE, game_plugins::world_map::chunk_gen::TileType};
+
+/// One chunk the way a `ChunkStore` keeps it, both blobs are already compressed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkRecord {
+    pub position: IVec2,
+    // one `TileType::id` per tile, column by column
+    pub tiles: Vec<u8>,
+    // a `ChunkEntities` as ron
+    pub entities: Vec<u8>,
+}
+
+impl ChunkRecord {
+    pub fn encode(
+        position: IVec2,
+        tiles: &[TileType],
+        entities: &ChunkEntities,
+    ) -> Result<Self, String> {
+        let tile_ids: Vec<u8> = tiles.iter().map(|tile| tile.id()).collect();
+        let entities = migrations::write(entities)?;
+
+        Ok(Self {
+            position,
+            tiles: lz4_flex::compress_prepend_size(&tile_ids),
+            entities: lz4_flex::compress_prepend_size(entities.as_bytes()),
+        })
+    }
+
+    /// the length of the tile blob as 4 bytes, the tile blob and then the entity blob
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.tiles.len() + self.entities.len());
+        bytes.extend_from_slice(&(self.tiles.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.tiles);
+        bytes.extend_from_slice(&self.entities);
+        bytes
+    }
+
+    pub fn from_bytes(position: IVec2, bytes: &[u8]) -> Result<Self, String> {
+        let broken = || format!("chunk {:?} is cut off", position);
+        let tiles_len = u32::from_le_bytes(bytes.get(..4).ok_or_else(broken)?.try_into().unwrap());
+        let tiles_end = 4 + tiles_len as usize;
+
+        Ok(Self {
+            position,
+            tiles: bytes.get(4..tiles_end).ok_or_else(broken)?.to_vec(),
+            entities: bytes[tiles_end..].to_vec(),
+        })
+    }
+
+    pub fn tiles(&self) -> Result<Vec<TileType>, String> {
+        let tile_ids = lz4_flex::decompress_size_prepended(&self.tiles)
+            .map_err(|e| format!("tiles of chunk {:?} are broken: {}", self.position, e))?;
+        if tile_ids.len() != (CHUNK_SIZE.x * CHUNK_SIZE.y) as usize {
+            return Err(format!(
+                "chunk {:?} has {} tiles instead of {}",
+                self.position,
+                tile_ids.len(),
+                CHUNK_SIZE.x * CHUNK_SIZE.y
+            ));
+        }
+
+        tile_ids
+            .into_iter()
+            .map(|id| TileType::from_id(id).ok_or_else(|| format!("unknown tile id {}", id)))
+            .collect()
+    }
+
+    /// the entities in the current chunk version, older ones get upgraded
+    pub fn entities(&self) -> Result<ChunkEntities, String> {
+        let content = lz4_flex::decompress_size_prepended(&self.entities)
+            .map_err(|e| format!("entities of chunk {:?} are broken: {}", self.position, e))?;
+        let content = String::from_utf8(content)
+            .map_err(|e| format!("entities of chunk {:?} are broken: {}", self.position, e))?;
+
+        migrations::parse(&CHUNK_MIGRATIONS.upgrade(content)?)
+    }
+}
+
+/// Somewhere the chunks of one world are kept while nobody is looking at them.
+pub trait ChunkStore: Send {
+    fn read(&mut self, position: IVec2) -> Result<Option<ChunkRecord>, String>;
+
+    /// writes all records at once, if anything fails none of them are written
+    fn write_batch(&mut self, records: &[ChunkRecord]) -> Result<(), String>;
+
+    /// every chunk that is in the store
+    fn positions(&mut self) -> Result<Vec<IVec2>, String>;
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkBackend {
+    #[default]
+    Sqlite,
+    Files,
+    Regions,
+}
+
+impl ChunkBackend {
+    pub const ALL: [ChunkBackend; 3] = [
+        ChunkBackend::Sqlite,
+        ChunkBackend::Files,
+        ChunkBackend::Regions,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChunkBackend::Sqlite => "SQLite",
+            ChunkBackend::Files => "Files",
+            ChunkBackend::Regions => "Region files",
+        }
+    }
+
+    pub fn next(&self) -> ChunkBackend {
+        match self {
+            ChunkBackend::Sqlite => ChunkBackend::Files,
+            ChunkBackend::Files => ChunkBackend::Regions,
+            ChunkBackend::Regions => ChunkBackend::Sqlite,
+        }
+    }
+
+    fn path(&self, slot: &str) -> PathBuf {
+        match self {
+            ChunkBackend::Sqlite => slot_path(slot).with_extension("db"),
+            ChunkBackend::Files => slot_path(slot).with_extension("chunks"),
+            ChunkBackend::Regions => slot_path(slot).with_extension("regions"),
+        }
+    }
+
+    /// the backend a world was saved with, if it has any chunks on disk
+    pub fn of_slot(slot: &str) -> Option<ChunkBackend> {
+        ChunkBackend::ALL
+            .into_iter()
+            .find(|backend| backend.path(slot).exists())
+    }
+
+    pub fn open(&self, slot: &str) -> Result<Box<dyn ChunkStore>, String> {
+        let path = self.path(slot);
+        Ok(match self {
+            ChunkBackend::Sqlite => Box::new(sqlite::SqliteChunkStore::open(&path)?),
+            ChunkBackend::Files => Box::new(files::FileChunkStore::open(&path)?),
+            ChunkBackend::Regions => Box::new(region::RegionChunkStore::open(&path)?),
+        })
+    }
+}
+
+/// throws away whatever chunks a slot has, in every backend
+pub fn delete_slot_chunks(slot: &str) -> Result<(), String> {
+    for backend in ChunkBackend::ALL {
+        let path = backend.path(slot);
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else if path.exists() {
+            fs::remove_file(&path)
+        } else {
+            continue;
+        };
+        removed.map_err(|e| format!("can't delete {}: {}", path.display(), e))?;
+    }
+    // what SQLite keeps next to the database while it is open
+    for extension in ["db-wal", "db-shm"] {
+        let _ = fs::remove_file(slot_path(slot).with_extension(extension));
+    }
+
+    Ok(())
+}
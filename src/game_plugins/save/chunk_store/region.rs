@@ -0,0 +1,357 @@
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use bevy::{log::warn, math::IVec2, utils::HashMap};
+
+use super::{ChunkRecord, ChunkStore};
+
+/// A region file holds this many chunks in each direction.
+pub const REGION_SIZE: i32 = 16;
+const CHUNKS_PER_REGION: usize = (REGION_SIZE * REGION_SIZE) as usize;
+
+const MAGIC: [u8; 4] = *b"ARPG";
+/// Bumped when the layout of region files changes.
+pub const REGION_VERSION: u32 = 1;
+const REGION_EXTENSION: &str = "region";
+
+// magic, version and then offset, length and checksum of every chunk
+const HEADER_LEN: usize = 8 + CHUNKS_PER_REGION * 12;
+
+/// Chunks grouped into `r.<x>.<y>.region` files of 16x16 chunks each, in one folder.
+///
+/// A region file starts with a header: `ARPG`, the region version and a table with
+/// offset, length and checksum of every chunk, all little endian `u32`s. An offset of 0
+/// means the chunk was never written. The chunks follow as `ChunkRecord::to_bytes`,
+/// which are compressed on their own already.
+pub struct RegionChunkStore {
+    folder: PathBuf,
+}
+
+pub fn region_of(chunk: IVec2) -> IVec2 {
+    IVec2::new(chunk.x.div_euclid(REGION_SIZE), chunk.y.div_euclid(REGION_SIZE))
+}
+
+fn index_in_region(chunk: IVec2) -> usize {
+    (chunk.y.rem_euclid(REGION_SIZE) * REGION_SIZE + chunk.x.rem_euclid(REGION_SIZE)) as usize
+}
+
+fn chunk_at(region: IVec2, index: usize) -> IVec2 {
+    region * REGION_SIZE + IVec2::new(index as i32 % REGION_SIZE, index as i32 / REGION_SIZE)
+}
+
+pub fn region_from_path(path: &Path) -> Option<IVec2> {
+    let name = path.file_name()?.to_str()?;
+    let mut parts = name.split('.');
+    if parts.next()? != "r" {
+        return None;
+    }
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    if parts.next()? != REGION_EXTENSION || parts.next().is_some() {
+        return None;
+    }
+
+    Some(IVec2::new(x, y))
+}
+
+// FNV-1a, good enough to notice flipped bits and half written chunks
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TableEntry {
+    offset: u32,
+    length: u32,
+    checksum: u32,
+}
+
+fn read_table(header: &[u8]) -> Result<Vec<TableEntry>, String> {
+    if header.len() < HEADER_LEN {
+        return Err(String::from("the header is cut off"));
+    }
+    if header[..4] != MAGIC {
+        return Err(String::from("this is not a region file"));
+    }
+    let version = read_u32(header, 4);
+    if version != REGION_VERSION {
+        return Err(format!("unknown region version {}", version));
+    }
+
+    Ok((0..CHUNKS_PER_REGION)
+        .map(|index| {
+            let at = 8 + index * 12;
+            TableEntry {
+                offset: read_u32(header, at),
+                length: read_u32(header, at + 4),
+                checksum: read_u32(header, at + 8),
+            }
+        })
+        .collect())
+}
+
+/// Something wrong with one chunk of a region, or the whole file if `chunk` is `None`.
+#[derive(Debug, Clone)]
+pub struct RegionProblem {
+    pub chunk: Option<IVec2>,
+    pub message: String,
+}
+
+/// All chunks of one region file in memory.
+pub struct Region {
+    pub position: IVec2,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl Region {
+    pub fn empty(position: IVec2) -> Self {
+        Self {
+            position,
+            chunks: vec![None; CHUNKS_PER_REGION],
+        }
+    }
+
+    /// Reads a region file. Chunks that are broken are left out and reported instead,
+    /// with `deep` their blobs also get decompressed and parsed to find every problem.
+    pub fn load(path: &Path, position: IVec2, deep: bool) -> Result<(Self, Vec<RegionProblem>), String> {
+        let mut region = Region::empty(position);
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok((region, Vec::new())),
+            Err(e) => return Err(format!("can't read {}: {}", path.display(), e)),
+        };
+
+        let table = match read_table(&bytes) {
+            Ok(table) => table,
+            Err(message) => {
+                let problem = RegionProblem {
+                    chunk: None,
+                    message,
+                };
+                return Ok((region, vec![problem]));
+            }
+        };
+
+        let mut problems = Vec::new();
+        for (index, entry) in table.iter().enumerate() {
+            if entry.offset == 0 {
+                continue;
+            }
+            let chunk = chunk_at(position, index);
+
+            match check_entry(&bytes, chunk, entry, deep) {
+                Ok(data) => region.chunks[index] = Some(data.to_vec()),
+                Err(message) => problems.push(RegionProblem {
+                    chunk: Some(chunk),
+                    message,
+                }),
+            }
+        }
+
+        Ok((region, problems))
+    }
+
+    pub fn set(&mut self, record: &ChunkRecord) {
+        self.chunks[index_in_region(record.position)] = Some(record.to_bytes());
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.iter().flatten().count()
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, data)| data.is_some())
+            .map(|(index, _)| chunk_at(self.position, index))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&REGION_VERSION.to_le_bytes());
+
+        let mut body = Vec::new();
+        for data in self.chunks.iter() {
+            let (offset, length, sum) = match data {
+                Some(data) => {
+                    let offset = HEADER_LEN + body.len();
+                    body.extend_from_slice(data);
+                    (offset as u32, data.len() as u32, checksum(data))
+                }
+                None => (0, 0, 0),
+            };
+            header.extend_from_slice(&offset.to_le_bytes());
+            header.extend_from_slice(&length.to_le_bytes());
+            header.extend_from_slice(&sum.to_le_bytes());
+        }
+
+        header.extend_from_slice(&body);
+        header
+    }
+
+    /// replaces the file in one go, a crash leaves either the old or the new region behind
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, self.to_bytes())
+            .map_err(|e| format!("can't write {}: {}", tmp_path.display(), e))?;
+        fs::rename(&tmp_path, path).map_err(|e| format!("can't write {}: {}", path.display(), e))
+    }
+}
+
+fn check_entry<'a>(
+    bytes: &'a [u8],
+    chunk: IVec2,
+    entry: &TableEntry,
+    deep: bool,
+) -> Result<&'a [u8], String> {
+    let start = entry.offset as usize;
+    let end = start + entry.length as usize;
+    if start < HEADER_LEN || end > bytes.len() {
+        return Err(format!(
+            "points to bytes {}..{} of a {} byte file",
+            start,
+            end,
+            bytes.len()
+        ));
+    }
+
+    let data = &bytes[start..end];
+    if checksum(data) != entry.checksum {
+        return Err(String::from("checksum does not match"));
+    }
+
+    if deep {
+        let record = ChunkRecord::from_bytes(chunk, data)?;
+        record.tiles()?;
+        record.entities()?;
+    }
+
+    Ok(data)
+}
+
+impl RegionChunkStore {
+    pub fn open(folder: &Path) -> Result<Self, String> {
+        fs::create_dir_all(folder).map_err(|e| format!("can't create {}: {}", folder.display(), e))?;
+
+        Ok(Self {
+            folder: folder.to_path_buf(),
+        })
+    }
+
+    pub fn region_path(&self, region: IVec2) -> PathBuf {
+        self.folder
+            .join(format!("r.{}.{}.{}", region.x, region.y, REGION_EXTENSION))
+    }
+
+    /// every region file in the folder with its position
+    pub fn regions(&self) -> Result<Vec<(IVec2, PathBuf)>, String> {
+        let entries = fs::read_dir(&self.folder)
+            .map_err(|e| format!("can't read {}: {}", self.folder.display(), e))?;
+
+        let mut regions: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter_map(|path| region_from_path(&path).map(|region| (region, path)))
+            .collect();
+        regions.sort_by_key(|(region, _)| (region.x, region.y));
+
+        Ok(regions)
+    }
+}
+
+impl ChunkStore for RegionChunkStore {
+    // only the header and the one chunk are read, not the whole region
+    fn read(&mut self, position: IVec2) -> Result<Option<ChunkRecord>, String> {
+        let path = self.region_path(region_of(position));
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("can't read {}: {}", path.display(), e)),
+        };
+        let broken = |e: String| format!("{} is broken: {}", path.display(), e);
+
+        let mut header = vec![0; HEADER_LEN];
+        file.read_exact(&mut header).map_err(|e| broken(e.to_string()))?;
+        let entry = read_table(&header).map_err(broken)?[index_in_region(position)];
+        if entry.offset == 0 {
+            return Ok(None);
+        }
+
+        let mut data = vec![0; entry.length as usize];
+        file.seek(SeekFrom::Start(entry.offset as u64))
+            .and_then(|_| file.read_exact(&mut data))
+            .map_err(|e| broken(e.to_string()))?;
+        if checksum(&data) != entry.checksum {
+            return Err(broken(format!("checksum of chunk {:?} does not match", position)));
+        }
+
+        ChunkRecord::from_bytes(position, &data).map(Some)
+    }
+
+    fn write_batch(&mut self, records: &[ChunkRecord]) -> Result<(), String> {
+        let mut by_region: HashMap<IVec2, Vec<&ChunkRecord>> = HashMap::new();
+        for record in records {
+            by_region.entry(region_of(record.position)).or_default().push(record);
+        }
+
+        // every touched region is written next to the old one first and only
+        // replaces it once all of them made it to disk
+        let mut written = Vec::new();
+        for (position, records) in by_region {
+            let path = self.region_path(position);
+            let (mut region, problems) = Region::load(&path, position, false)?;
+            // without a readable header the chunks in there may still be rescued by hand,
+            // so the file is kept as `.corrupt` like `region_tool --repair` does
+            let corrupt = problems.iter().any(|problem| problem.chunk.is_none());
+            for problem in problems {
+                warn!("dropping broken data in {}: {:?}", path.display(), problem);
+            }
+            for record in records {
+                region.set(record);
+            }
+
+            let tmp_path = path.with_extension("tmp");
+            if let Err(e) = fs::write(&tmp_path, region.to_bytes()) {
+                for (tmp_path, _, _) in written {
+                    let _ = fs::remove_file(tmp_path);
+                }
+                return Err(format!("can't write {}: {}", tmp_path.display(), e));
+            }
+            written.push((tmp_path, path, corrupt));
+        }
+
+        for (tmp_path, path, corrupt) in written {
+            if corrupt {
+                let corrupt_path = path.with_extension("corrupt");
+                fs::rename(&path, &corrupt_path)
+                    .map_err(|e| format!("can't move {} aside: {}", path.display(), e))?;
+                warn!("moved the broken {} to {}", path.display(), corrupt_path.display());
+            }
+            fs::rename(&tmp_path, &path)
+                .map_err(|e| format!("can't write {}: {}", path.display(), e))?;
+        }
+
+        Ok(())
+    }
+
+    fn positions(&mut self) -> Result<Vec<IVec2>, String> {
+        let mut positions = Vec::new();
+        for (position, path) in self.regions()? {
+            let (region, _) = Region::load(&path, position, false)?;
+            positions.extend(region.chunks());
+        }
+
+        Ok(positions)
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use bevy::math::IVec2;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{ChunkRecord, ChunkStore};
+
+/// All chunks of a world in one SQLite database, one row per chunk.
+pub struct SqliteChunkStore {
+    connection: Connection,
+}
+
+impl SqliteChunkStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let connection = Connection::open(path)
+            .map_err(|e| format!("can't open {}: {}", path.display(), e))?;
+
+        // WAL lets the game read chunks while the writer thread is in the middle of a batch
+        connection
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                PRAGMA synchronous = NORMAL;
+                CREATE TABLE IF NOT EXISTS chunks (
+                    x INTEGER NOT NULL,
+                    y INTEGER NOT NULL,
+                    tiles BLOB NOT NULL,
+                    entities BLOB NOT NULL,
+                    PRIMARY KEY (x, y)
+                ) WITHOUT ROWID;",
+            )
+            .map_err(|e| format!("can't set up {}: {}", path.display(), e))?;
+
+        Ok(Self { connection })
+    }
+}
+
+impl ChunkStore for SqliteChunkStore {
+    fn read(&mut self, position: IVec2) -> Result<Option<ChunkRecord>, String> {
+        self.connection
+            .query_row(
+                "SELECT tiles, entities FROM chunks WHERE x = ?1 AND y = ?2",
+                params![position.x, position.y],
+                |row| {
+                    Ok(ChunkRecord {
+                        position,
+                        tiles: row.get(0)?,
+                        entities: row.get(1)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| format!("can't read chunk {:?}: {}", position, e))
+    }
+
+    fn write_batch(&mut self, records: &[ChunkRecord]) -> Result<(), String> {
+        let transaction = self
+            .connection
+            .transaction()
+            .map_err(|e| format!("can't start a transaction: {}", e))?;
+
+        {
+            let mut insert = transaction
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO chunks (x, y, tiles, entities) VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(|e| e.to_string())?;
+
+            for record in records {
+                insert
+                    .execute(params![
+                        record.position.x,
+                        record.position.y,
+                        record.tiles,
+                        record.entities
+                    ])
+                    .map_err(|e| format!("can't write chunk {:?}: {}", record.position, e))?;
+            }
+        }
+
+        // dropping the transaction without commit rolls everything back
+        transaction
+            .commit()
+            .map_err(|e| format!("can't commit {} chunks: {}", records.len(), e))
+    }
+
+    fn positions(&mut self) -> Result<Vec<IVec2>, String> {
+        let mut select = self
+            .connection
+            .prepare("SELECT x, y FROM chunks")
+            .map_err(|e| e.to_string())?;
+
+        let positions = select
+            .query_map([], |row| Ok(IVec2::new(row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        Ok(positions)
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    thread::JoinHandle,
+};
+
+use bevy::{log::error, math::IVec2, utils::HashMap};
+
+use super::{ChunkRecord, ChunkStore};
+use crate::game_plugins::{save::format::ChunkEntities, world_map::chunk_gen::TileType};
+
+/// A chunk on its way to the writer thread.
+pub struct ChunkWrite {
+    pub position: IVec2,
+    pub tiles: Vec<TileType>,
+    pub entities: ChunkEntities,
+    // tells apart several writes of the same chunk
+    pub ticket: u64,
+}
+
+/// Owns a `ChunkStore` on a thread of its own. Everything that queued up while the
+/// last batch was written goes to disk in the next transaction, so the game never waits.
+pub struct ChunkWriter {
+    jobs: Option<Sender<Vec<ChunkWrite>>>,
+    written: Mutex<Receiver<Vec<(IVec2, u64)>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ChunkWriter {
+    pub fn spawn(mut store: Box<dyn ChunkStore>) -> Self {
+        let (jobs, job_receiver) = channel::<Vec<ChunkWrite>>();
+        let (written_sender, written) = channel();
+
+        let thread = std::thread::Builder::new()
+            .name(String::from("chunk writer"))
+            .spawn(move || {
+                while let Ok(first) = job_receiver.recv() {
+                    // only the newest write of each chunk matters
+                    let mut batch: HashMap<IVec2, ChunkWrite> = HashMap::new();
+                    for chunk in first.into_iter().chain(job_receiver.try_iter().flatten()) {
+                        batch.insert(chunk.position, chunk);
+                    }
+
+                    let records: Result<Vec<ChunkRecord>, String> = batch
+                        .values()
+                        .map(|chunk| ChunkRecord::encode(chunk.position, &chunk.tiles, &chunk.entities))
+                        .collect();
+
+                    match records.and_then(|records| store.write_batch(&records)) {
+                        Ok(()) => {
+                            let tickets = batch.values().map(|chunk| (chunk.position, chunk.ticket));
+                            // nobody listens anymore once the world is closed
+                            let _ = written_sender.send(tickets.collect());
+                        }
+                        Err(e) => error!("writing {} chunks failed: {}", batch.len(), e),
+                    }
+                }
+            })
+            .expect("can't start the chunk writer thread");
+
+        Self {
+            jobs: Some(jobs),
+            written: Mutex::new(written),
+            thread: Some(thread),
+        }
+    }
+
+    pub fn write(&self, chunks: Vec<ChunkWrite>) {
+        if chunks.is_empty() {
+            return;
+        }
+        if let Some(jobs) = &self.jobs {
+            if jobs.send(chunks).is_err() {
+                error!("the chunk writer thread is gone, chunks were not written");
+            }
+        }
+    }
+
+    /// chunks that made it to disk since the last call, with the ticket they were written with
+    pub fn written(&self) -> Vec<(IVec2, u64)> {
+        self.written.lock().unwrap().try_iter().flatten().collect()
+    }
+}
+
+impl Drop for ChunkWriter {
+    // closing the channel lets the thread finish what is queued, then it stops
+    fn drop(&mut self) {
+        self.jobs.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("the chunk writer thread panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::migrations::{self, SAVE_MIGRATIONS};
+use crate::game_plugins::{
+    data::saves_path,
+    daytime::WorldClock,
+    inventory::Item,
+    pig::PigState,
+    tree::{TreeStage, TreeState},
+    world_map::world_gen::RngJesus,
+};
+
+/// Bumped every time the layout of `SaveFile` changes, together with a new
+/// step in `SAVE_MIGRATIONS` that upgrades the previous version.
+pub const SAVE_VERSION: u32 = 5;
+/// Same for the entities of a chunk that live in the chunk store of a world.
+pub const CHUNK_VERSION: u32 = 2;
+const SAVE_EXTENSION: &str = "ron";
+
+/// Everything that is written to disk for one save slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub info: SaveInfo,
+    pub world: WorldSave,
+    pub player: PlayerSave,
+    pub money: u32,
+    // seconds the world has been played
+    pub play_time: f32,
+    pub clock: ClockSave,
+    // every chunk the player has seen, sorted, the map shows these
+    pub explored: Vec<(i32, i32)>,
+    // tiles marked on the map
+    pub waypoints: Vec<(i32, i32)>,
+    // chunks that are not in the chunk store of the world, only older saves have these
+    pub chunks: Vec<ChunkSave>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveInfo {
+    pub name: String,
+    // the game version that wrote this save, handy for bug reports
+    pub game_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldSave {
+    pub seed: u32,
+    pub seed2: u32,
+    pub biom_seed: u32,
+}
+
+impl From<&RngJesus> for WorldSave {
+    fn from(rng_jesus: &RngJesus) -> Self {
+        Self {
+            seed: rng_jesus.seed,
+            seed2: rng_jesus.seed2,
+            biom_seed: rng_jesus.biom_seed,
+        }
+    }
+}
+
+impl From<&WorldSave> for RngJesus {
+    fn from(world: &WorldSave) -> Self {
+        Self {
+            seed: world.seed,
+            seed2: world.seed2,
+            biom_seed: world.biom_seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockSave {
+    pub day: u32,
+    pub hour: f32,
+}
+
+impl Default for ClockSave {
+    fn default() -> Self {
+        (&WorldClock::default()).into()
+    }
+}
+
+impl From<&WorldClock> for ClockSave {
+    fn from(clock: &WorldClock) -> Self {
+        Self {
+            day: clock.day,
+            hour: clock.hour,
+        }
+    }
+}
+
+impl From<&ClockSave> for WorldClock {
+    fn from(clock: &ClockSave) -> Self {
+        WorldClock::new(clock.day, clock.hour)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub position: (f32, f32),
+    pub health: u32,
+    pub max_health: u32,
+    pub inventory: Vec<(Item, u32)>,
+}
+
+/// The trees and animals living on one chunk.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkSave {
+    pub position: (i32, i32),
+    pub trees: Vec<TreeSave>,
+    pub pigs: Vec<PigSave>,
+}
+
+/// The entities of one chunk as written into the chunk store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkEntities {
+    pub version: u32,
+    // `PlayTime` when the chunk was unloaded, so trees can keep growing meanwhile
+    pub unloaded_at: f32,
+    pub trees: Vec<TreeSave>,
+    pub pigs: Vec<PigSave>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeSave {
+    pub tile: (i32, i32),
+    pub stage: TreeStage,
+    pub health: usize,
+    pub growth_elapsed: f32,
+}
+
+impl From<&TreeState> for TreeSave {
+    fn from(tree: &TreeState) -> Self {
+        Self {
+            tile: (tree.tile.x, tree.tile.y),
+            stage: tree.stage,
+            health: tree.health,
+            growth_elapsed: tree.growth_elapsed,
+        }
+    }
+}
+
+impl From<&TreeSave> for TreeState {
+    fn from(tree: &TreeSave) -> Self {
+        Self {
+            tile: IVec2::new(tree.tile.0, tree.tile.1),
+            stage: tree.stage,
+            health: tree.health,
+            growth_elapsed: tree.growth_elapsed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PigSave {
+    pub position: (f32, f32),
+    pub growth_elapsed: Option<f32>,
+    pub fed_elapsed: Option<f32>,
+    pub breed_cooldown_elapsed: Option<f32>,
+}
+
+impl From<&PigState> for PigSave {
+    fn from(pig: &PigState) -> Self {
+        Self {
+            position: (pig.position.x, pig.position.y),
+            growth_elapsed: pig.growth_elapsed,
+            fed_elapsed: pig.fed_elapsed,
+            breed_cooldown_elapsed: pig.breed_cooldown_elapsed,
+        }
+    }
+}
+
+impl From<&PigSave> for PigState {
+    fn from(pig: &PigSave) -> Self {
+        Self {
+            position: Vec2::new(pig.position.0, pig.position.1),
+            growth_elapsed: pig.growth_elapsed,
+            fed_elapsed: pig.fed_elapsed,
+            breed_cooldown_elapsed: pig.breed_cooldown_elapsed,
+        }
+    }
+}
+
+pub fn slot_path(slot: &str) -> PathBuf {
+    saves_path().join(format!("{}.{}", slot, SAVE_EXTENSION))
+}
+
+/// names of all save slots on disk, sorted
+pub fn list_slots() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(saves_path()) else {
+        return Vec::new();
+    };
+
+    let mut slots: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == SAVE_EXTENSION))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    slots.sort();
+
+    slots
+}
+
+/// the first "World <n>" name that is not taken yet
+pub fn free_slot_name() -> String {
+    let slots = list_slots();
+    (1..)
+        .map(|n| format!("World {}", n))
+        .find(|name| !slots.contains(name))
+        .unwrap()
+}
+
+pub fn write_save(slot: &str, save: &SaveFile) -> Result<(), String> {
+    fs::create_dir_all(saves_path()).map_err(|e| format!("can't create saves folder: {}", e))?;
+
+    let content = migrations::write(save).map_err(|e| format!("can't serialize save: {}", e))?;
+
+    // write next to the old save first, so a crash never leaves a half written slot behind
+    let path = slot_path(slot);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("can't write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("can't write {}: {}", path.display(), e))
+}
+
+/// reads a save of any known version, older ones get upgraded on the way
+pub fn parse_save(content: &str) -> Result<SaveFile, String> {
+    let content = SAVE_MIGRATIONS.upgrade(content.to_string())?;
+    migrations::parse(&content)
+}
+
+pub fn read_save(slot: &str) -> Result<SaveFile, String> {
+    let path = slot_path(slot);
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+
+    parse_save(&content).map_err(|e| format!("can't load {}: {}", path.display(), e))
+}
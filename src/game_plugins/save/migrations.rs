@@ -0,0 +1,317 @@
+use bevy::log::debug;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::format::{
+    ChunkEntities, ChunkSave, ClockSave, PigSave, SaveFile, SaveInfo, CHUNK_VERSION, SAVE_VERSION,
+};
+
+/// One step from a format version to the next one. It gets the whole file of
+/// version `from` and returns the same data written in the next version.
+pub struct Migration {
+    pub from: u32,
+    pub upgrade: fn(&str) -> Result<String, String>,
+}
+
+/// All steps of one kind of file, so old files can be upgraded one version at a time.
+pub struct Migrations {
+    pub kind: &'static str,
+    pub current: u32,
+    pub steps: &'static [Migration],
+}
+
+pub const SAVE_MIGRATIONS: Migrations = Migrations {
+    kind: "save",
+    current: SAVE_VERSION,
+    steps: &[
+        Migration {
+            from: 1,
+            upgrade: save_v1_to_v2,
+        },
+        Migration {
+            from: 2,
+            upgrade: save_v2_to_v3,
+        },
+        Migration {
+            from: 3,
+            upgrade: save_v3_to_v4,
+        },
+        Migration {
+            from: 4,
+            upgrade: save_v4_to_v5,
+        },
+    ],
+};
+
+pub const CHUNK_MIGRATIONS: Migrations = Migrations {
+    kind: "chunk",
+    current: CHUNK_VERSION,
+    steps: &[Migration {
+        from: 1,
+        upgrade: chunk_v1_to_v2,
+    }],
+};
+
+// every file format keeps its `version` at the top level, whatever else changes
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+pub fn parse<T: DeserializeOwned>(content: &str) -> Result<T, String> {
+    ron::from_str(content).map_err(|e| e.to_string())
+}
+
+pub fn write<T: Serialize>(value: &T) -> Result<String, String> {
+    ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())
+}
+
+impl Migrations {
+    pub fn version_of(&self, content: &str) -> Result<u32, String> {
+        parse::<VersionProbe>(content)
+            .map(|probe| probe.version)
+            .map_err(|e| format!("{} file has no version: {}", self.kind, e))
+    }
+
+    /// runs every step between the version of `content` and the current one
+    pub fn upgrade(&self, mut content: String) -> Result<String, String> {
+        let mut version = self.version_of(&content)?;
+        if version > self.current {
+            return Err(format!(
+                "{} file was written by a newer version of the game (version {}, we know {})",
+                self.kind, version, self.current
+            ));
+        }
+
+        while version < self.current {
+            let step = self
+                .steps
+                .iter()
+                .find(|step| step.from == version)
+                .ok_or_else(|| format!("no way to upgrade a {} file from version {}", self.kind, version))?;
+
+            content = (step.upgrade)(&content)
+                .map_err(|e| format!("upgrading {} file from version {}: {}", self.kind, version, e))?;
+
+            let next = self.version_of(&content)?;
+            if next <= version {
+                return Err(format!(
+                    "upgrading {} file from version {} did not change its version",
+                    self.kind, version
+                ));
+            }
+            debug!("upgraded {} file from version {} to {}", self.kind, version, next);
+            version = next;
+        }
+
+        Ok(content)
+    }
+}
+
+/// The save layouts of older versions, exactly as they were written back then.
+/// When a type used in here changes in `format`, copy its old shape in here first.
+mod v1 {
+    use serde::Deserialize;
+
+    use super::super::format::{PlayerSave, WorldSave};
+    use super::v4::ChunkSave;
+
+    #[derive(Deserialize)]
+    pub struct SaveFile {
+        pub name: String,
+        pub world: WorldSave,
+        pub player: PlayerSave,
+        pub money: u32,
+        pub play_time: f32,
+        pub chunks: Vec<ChunkSave>,
+    }
+}
+
+mod v2 {
+    use serde::{Deserialize, Serialize};
+
+    use super::super::format::{PlayerSave, SaveInfo, WorldSave};
+    use super::v4::ChunkSave;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveFile {
+        pub version: u32,
+        pub info: SaveInfo,
+        pub world: WorldSave,
+        pub player: PlayerSave,
+        pub money: u32,
+        pub play_time: f32,
+        pub chunks: Vec<ChunkSave>,
+    }
+}
+
+mod v3 {
+    use serde::{Deserialize, Serialize};
+
+    use super::super::format::{ClockSave, PlayerSave, SaveInfo, WorldSave};
+    use super::v4::ChunkSave;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveFile {
+        pub version: u32,
+        pub info: SaveInfo,
+        pub world: WorldSave,
+        pub player: PlayerSave,
+        pub money: u32,
+        pub play_time: f32,
+        pub clock: ClockSave,
+        pub chunks: Vec<ChunkSave>,
+    }
+}
+
+mod v4 {
+    use serde::{Deserialize, Serialize};
+
+    use super::super::format::{ClockSave, PlayerSave, SaveInfo, TreeSave, WorldSave};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SaveFile {
+        pub version: u32,
+        pub info: SaveInfo,
+        pub world: WorldSave,
+        pub player: PlayerSave,
+        pub money: u32,
+        pub play_time: f32,
+        pub clock: ClockSave,
+        pub explored: Vec<(i32, i32)>,
+        pub waypoints: Vec<(i32, i32)>,
+        pub chunks: Vec<ChunkSave>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ChunkSave {
+        pub position: (i32, i32),
+        pub trees: Vec<TreeSave>,
+        pub pigs: Vec<PigSave>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct PigSave {
+        pub position: (f32, f32),
+        pub growth_elapsed: Option<f32>,
+        pub fed_elapsed: Option<f32>,
+    }
+}
+
+/// The chunk entity layouts of older versions, same rules as for the saves.
+mod chunk_v1 {
+    use serde::Deserialize;
+
+    use super::super::format::TreeSave;
+    use super::v4::PigSave;
+
+    #[derive(Deserialize)]
+    pub struct ChunkEntities {
+        pub unloaded_at: f32,
+        pub trees: Vec<TreeSave>,
+        pub pigs: Vec<PigSave>,
+    }
+}
+
+/// version 2 moved the slot name into `info` and remembers which game wrote the save
+fn save_v1_to_v2(content: &str) -> Result<String, String> {
+    let old: v1::SaveFile = parse(content)?;
+
+    write(&v2::SaveFile {
+        version: 2,
+        info: SaveInfo {
+            name: old.name,
+            game_version: String::from("unknown"),
+        },
+        world: old.world,
+        player: old.player,
+        money: old.money,
+        play_time: old.play_time,
+        chunks: old.chunks,
+    })
+}
+
+/// version 3 has the world clock, older worlds wake up on the first morning
+fn save_v2_to_v3(content: &str) -> Result<String, String> {
+    let old: v2::SaveFile = parse(content)?;
+
+    write(&v3::SaveFile {
+        version: 3,
+        info: old.info,
+        world: old.world,
+        player: old.player,
+        money: old.money,
+        play_time: old.play_time,
+        clock: ClockSave::default(),
+        chunks: old.chunks,
+    })
+}
+
+/// version 4 remembers the explored chunks and waypoints for the map. Older saves
+/// at least know the chunks they carry, the player must have been there.
+fn save_v3_to_v4(content: &str) -> Result<String, String> {
+    let old: v3::SaveFile = parse(content)?;
+
+    let mut explored: Vec<(i32, i32)> = old.chunks.iter().map(|chunk| chunk.position).collect();
+    explored.sort();
+
+    write(&v4::SaveFile {
+        version: 4,
+        info: old.info,
+        world: old.world,
+        player: old.player,
+        money: old.money,
+        play_time: old.play_time,
+        clock: old.clock,
+        explored,
+        waypoints: Vec::new(),
+        chunks: old.chunks,
+    })
+}
+
+// pigs from before the breeding cooldown was saved never bred not long ago
+fn pig_v4_to_v5(pig: v4::PigSave) -> PigSave {
+    PigSave {
+        position: pig.position,
+        growth_elapsed: pig.growth_elapsed,
+        fed_elapsed: pig.fed_elapsed,
+        breed_cooldown_elapsed: None,
+    }
+}
+
+/// version 5 remembers which pigs bred a moment ago
+fn save_v4_to_v5(content: &str) -> Result<String, String> {
+    let old: v4::SaveFile = parse(content)?;
+
+    write(&SaveFile {
+        version: 5,
+        info: old.info,
+        world: old.world,
+        player: old.player,
+        money: old.money,
+        play_time: old.play_time,
+        clock: old.clock,
+        explored: old.explored,
+        waypoints: old.waypoints,
+        chunks: old
+            .chunks
+            .into_iter()
+            .map(|chunk| ChunkSave {
+                position: chunk.position,
+                trees: chunk.trees,
+                pigs: chunk.pigs.into_iter().map(pig_v4_to_v5).collect(),
+            })
+            .collect(),
+    })
+}
+
+/// chunk version 2 remembers which pigs bred a moment ago, like save version 5
+fn chunk_v1_to_v2(content: &str) -> Result<String, String> {
+    let old: chunk_v1::ChunkEntities = parse(content)?;
+
+    write(&ChunkEntities {
+        version: 2,
+        unloaded_at: old.unloaded_at,
+        trees: old.trees,
+        pigs: old.pigs.into_iter().map(pig_v4_to_v5).collect(),
+    })
+}
@@ -0,0 +1,341 @@
+pub mod chunk_store;
+pub mod format;
+pub mod migrations;
+pub mod streaming;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use self::{
+    chunk_store::{delete_slot_chunks, ChunkBackend},
+    format::{
+        free_slot_name, list_slots, read_save, write_save, ChunkEntities, ChunkSave, PlayerSave,
+        SaveFile, SaveInfo, CHUNK_VERSION, SAVE_VERSION,
+    },
+    streaming::{
+        close_world_storage, fetch_stored_chunks, open_world_storage,
+        persist_unloaded_chunks, stored_chunk_entities, WorldStorage,
+    },
+};
+use super::{
+    actions::{Action, ActionState},
+    combat::Health,
+    daytime::WorldClock,
+    economy::Wallet,
+    inventory::Inventory,
+    movement::Velocity,
+    pig::{Pig, PigChunkStore, PigState},
+    player::{Player, PlayerTeleported},
+    tree::{Tree, TreeChunk, TreeChunkStore, TreeState},
+    world_map::{
+        chunk_gen::chunk_tiles,
+        map_screen::Waypoints,
+        minimap::ExploredChunks,
+        utils::{tile_at_world_pos, tiles_to_chunks},
+        world_gen::{ChunkManager, ChunkSet, RngJesus},
+    },
+};
+use crate::game_state::GameState;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayTime>()
+            .init_resource::<CurrentSlot>()
+            .init_resource::<SaveSlots>()
+            .init_resource::<ChunkBackend>()
+            .add_event::<SaveGame>()
+            .add_event::<LoadGame>()
+            .add_systems(
+                OnEnter(GameState::MENU),
+                (refresh_save_slots, reset_play_time, close_world_storage),
+            )
+            .add_systems(
+                OnExit(GameState::MENU),
+                (apply_pending_load, open_world_storage).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    tick_play_time,
+                    quick_save,
+                    fetch_stored_chunks.in_set(ChunkSet::Fetch),
+                    persist_unloaded_chunks.in_set(ChunkSet::Persist),
+                )
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(
+                Update,
+                (
+                    save_game.run_if(not(in_state(GameState::MENU))),
+                    load_game.run_if(in_state(GameState::MENU)),
+                ),
+            );
+    }
+}
+
+/// Seconds spent in the current world, without the time in menus.
+#[derive(Resource, Debug, Default)]
+pub struct PlayTime(pub f32);
+
+/// Name of the slot the current world is saved to.
+#[derive(Resource, Debug, Default)]
+pub struct CurrentSlot(pub String);
+
+/// All save slots found in the saves folder, for the load menu.
+#[derive(Resource, Debug, Default)]
+pub struct SaveSlots(pub Vec<String>);
+
+/// Writes the running world into `CurrentSlot`.
+#[derive(Event, Debug, Default)]
+pub struct SaveGame;
+
+/// Starts the world stored in the given slot.
+#[derive(Event, Debug)]
+pub struct LoadGame {
+    pub slot: String,
+}
+
+/// A save that was read in the menu and gets applied as soon as the game starts.
+#[derive(Resource, Debug)]
+struct PendingLoad(SaveFile);
+
+fn refresh_save_slots(mut slots: ResMut<SaveSlots>) {
+    slots.0 = list_slots();
+}
+
+fn reset_play_time(mut play_time: ResMut<PlayTime>) {
+    play_time.0 = 0.;
+}
+
+fn tick_play_time(time: Res<Time>, mut play_time: ResMut<PlayTime>) {
+    play_time.0 += time.delta_seconds();
+}
+
+fn quick_save(actions: Res<ActionState>, mut save: EventWriter<SaveGame>) {
+    if actions.just_pressed(Action::QuickSave) {
+        save.send(SaveGame);
+    }
+}
+
+/// writes the player and the world into the current slot. With a chunk store every
+/// chunk goes in there, without one they all end up in the save file itself.
+fn save_game(
+    mut save: EventReader<SaveGame>,
+    rng_jesus: Res<RngJesus>,
+    wallet: Res<Wallet>,
+    play_time: Res<PlayTime>,
+    clock: Res<WorldClock>,
+    explored: Res<ExploredChunks>,
+    waypoints: Res<Waypoints>,
+    current_slot: Res<CurrentSlot>,
+    chunk_manager: Res<ChunkManager>,
+    storage: Option<ResMut<WorldStorage>>,
+    mut tree_store: ResMut<TreeChunkStore>,
+    mut pig_store: ResMut<PigChunkStore>,
+    player: Query<(&Transform, &Health, &Inventory), With<Player>>,
+    trees: Query<&Tree>,
+    pigs: Query<(&Transform, &Pig)>,
+) {
+    if save.read().last().is_none() {
+        return;
+    }
+
+    let mut chunks: HashMap<IVec2, ChunkEntities> = HashMap::new();
+
+    // unloaded chunks first, their trees get the growth they would have had until now
+    let stored: Vec<IVec2> = tree_store
+        .chunks
+        .keys()
+        .chain(pig_store.chunks.keys())
+        .copied()
+        .collect();
+    for position in stored {
+        let entities = stored_chunk_entities(position, &play_time, &tree_store, &pig_store);
+        chunks.insert(position, entities);
+    }
+
+    // the spawned chunks are written even when nothing lives on them anymore,
+    // otherwise they would be filled with freshly generated trees and pigs again
+    for position in chunk_manager.spawned_chunks.keys() {
+        chunks.insert(
+            *position,
+            ChunkEntities {
+                version: CHUNK_VERSION,
+                unloaded_at: play_time.0,
+                trees: Vec::new(),
+                pigs: Vec::new(),
+            },
+        );
+    }
+    for tree in trees.iter() {
+        if let Some(chunk) = chunks.get_mut(&tree.chunk()) {
+            chunk.trees.push((&tree.to_state()).into());
+        }
+    }
+    for (transform, pig) in pigs.iter() {
+        let position = transform.translation.truncate();
+        match chunks.get_mut(&tiles_to_chunks(tile_at_world_pos(position))) {
+            Some(chunk) => chunk.pigs.push((&pig.to_state(position)).into()),
+            None => warn!("the pig at {:?} is not on a spawned chunk, it is not saved", position),
+        }
+    }
+
+    let embedded_chunks = match storage {
+        Some(mut storage) => {
+            tree_store.chunks.clear();
+            pig_store.chunks.clear();
+            storage.write(
+                chunks
+                    .into_iter()
+                    .map(|(position, entities)| {
+                        (position, chunk_tiles(&rng_jesus, position), entities)
+                    })
+                    .collect(),
+            );
+            Vec::new()
+        }
+        None => {
+            let mut embedded: Vec<ChunkSave> = chunks
+                .into_iter()
+                .map(|(position, entities)| ChunkSave {
+                    position: (position.x, position.y),
+                    trees: entities.trees,
+                    pigs: entities.pigs,
+                })
+                .collect();
+            embedded.sort_by_key(|chunk| chunk.position);
+            embedded
+        }
+    };
+
+    let (transform, health, inventory) = player.single();
+    let mut items: Vec<_> = inventory.items.iter().map(|(item, count)| (*item, *count)).collect();
+    items.sort_by_key(|(item, _)| item.name());
+    let mut explored: Vec<_> = explored.0.iter().map(|chunk| (chunk.x, chunk.y)).collect();
+    explored.sort();
+
+    let save_file = SaveFile {
+        version: SAVE_VERSION,
+        info: SaveInfo {
+            name: current_slot.0.clone(),
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        world: rng_jesus.as_ref().into(),
+        player: PlayerSave {
+            position: (transform.translation.x, transform.translation.y),
+            health: health.current,
+            max_health: health.max,
+            inventory: items,
+        },
+        money: wallet.money,
+        play_time: play_time.0,
+        clock: clock.as_ref().into(),
+        explored,
+        waypoints: waypoints.0.iter().map(|tile| (tile.x, tile.y)).collect(),
+        chunks: embedded_chunks,
+    };
+
+    match write_save(&current_slot.0, &save_file) {
+        Ok(()) => info!("saved the world to slot {:?}", current_slot.0),
+        Err(e) => error!("saving failed: {}", e),
+    }
+}
+
+/// reads the save while still in the menu, so the world gets generated with the right seed
+fn load_game(
+    mut commands: Commands,
+    mut load: EventReader<LoadGame>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(load) = load.read().last() else {
+        return;
+    };
+
+    let save_file = match read_save(&load.slot) {
+        Ok(save_file) => save_file,
+        Err(e) => {
+            error!("loading failed: {}", e);
+            return;
+        }
+    };
+
+    info!("loading slot {:?}", load.slot);
+    commands.insert_resource(RngJesus::from(&save_file.world));
+    commands.insert_resource(CurrentSlot(load.slot.clone()));
+    commands.insert_resource(PendingLoad(save_file));
+    next_state.set(GameState::GAME);
+}
+
+/// fills the chunk stores before the first chunk is spawned, so the saved trees
+/// and pigs are used instead of freshly generated ones
+fn apply_pending_load(
+    mut commands: Commands,
+    pending: Option<Res<PendingLoad>>,
+    mut current_slot: ResMut<CurrentSlot>,
+    mut wallet: ResMut<Wallet>,
+    mut play_time: ResMut<PlayTime>,
+    mut clock: ResMut<WorldClock>,
+    mut explored: ResMut<ExploredChunks>,
+    mut waypoints: ResMut<Waypoints>,
+    mut tree_store: ResMut<TreeChunkStore>,
+    mut pig_store: ResMut<PigChunkStore>,
+    mut teleported: EventWriter<PlayerTeleported>,
+    mut player: Query<(&mut Transform, &mut Velocity, &mut Health, &mut Inventory), With<Player>>,
+) {
+    let Some(pending) = pending else {
+        // a new world, give it a slot of its own. Chunks left over from a world that
+        // was never saved under this name must not show up in it.
+        current_slot.0 = free_slot_name();
+        if let Err(e) = delete_slot_chunks(&current_slot.0) {
+            error!("{}", e);
+        }
+        return;
+    };
+    commands.remove_resource::<PendingLoad>();
+    let save_file = &pending.0;
+
+    for chunk in save_file.chunks.iter() {
+        let position = IVec2::new(chunk.position.0, chunk.position.1);
+        tree_store.chunks.insert(
+            position,
+            TreeChunk {
+                trees: chunk.trees.iter().map(TreeState::from).collect(),
+                unloaded_at: save_file.play_time,
+            },
+        );
+        pig_store
+            .chunks
+            .insert(position, chunk.pigs.iter().map(PigState::from).collect());
+    }
+
+    let (mut transform, mut velocity, mut health, mut inventory) = player.single_mut();
+    let (x, y) = save_file.player.position;
+    transform.translation.x = x;
+    transform.translation.y = y;
+    *velocity = Velocity::default();
+    teleported.send(PlayerTeleported);
+    *health = Health {
+        current: save_file.player.health,
+        max: save_file.player.max_health,
+    };
+    *inventory = Inventory::default();
+    for (item, count) in save_file.player.inventory.iter() {
+        inventory.add(*item, *count);
+    }
+
+    wallet.money = save_file.money;
+    play_time.0 = save_file.play_time;
+    *clock = (&save_file.clock).into();
+    // the tiles of these are generated again by the minimap
+    explored.0 = save_file
+        .explored
+        .iter()
+        .map(|(x, y)| IVec2::new(*x, *y))
+        .collect();
+    waypoints.0 = save_file
+        .waypoints
+        .iter()
+        .map(|(x, y)| IVec2::new(*x, *y))
+        .collect();
+}
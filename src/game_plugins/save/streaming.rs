@@ -0,0 +1,217 @@
+use std::sync::Mutex;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    chunk_store::{
+        writer::{ChunkWrite, ChunkWriter},
+        ChunkBackend, ChunkStore,
+    },
+    format::{ChunkEntities, CHUNK_VERSION},
+    CurrentSlot, PlayTime,
+};
+use crate::{
+    game_plugins::{
+        pig::{PigChunkStore, PigState},
+        tree::{TreeChunk, TreeChunkStore, TreeState},
+        world_map::{
+            chunk_gen::{chunk_tiles, TileType},
+            world_gen::{ChunkDespawned, ChunkSpawned, RngJesus},
+        },
+    },
+};
+
+/// The chunk store of the world that is played right now. Despawned chunks are
+/// written to it in the background, spawned ones are read back from it.
+#[derive(Resource)]
+pub struct WorldStorage {
+    reader: Mutex<Box<dyn ChunkStore>>,
+    writer: ChunkWriter,
+    // chunks handed to the writer that might not be on disk yet
+    in_flight: HashMap<IVec2, (u64, ChunkEntities)>,
+    next_ticket: u64,
+}
+
+impl WorldStorage {
+    pub fn open(backend: ChunkBackend, slot: &str) -> Result<Self, String> {
+        Ok(Self {
+            reader: Mutex::new(backend.open(slot)?),
+            writer: ChunkWriter::spawn(backend.open(slot)?),
+            in_flight: HashMap::new(),
+            next_ticket: 0,
+        })
+    }
+
+    pub fn write(&mut self, chunks: Vec<(IVec2, Vec<TileType>, ChunkEntities)>) {
+        let mut writes = Vec::with_capacity(chunks.len());
+        for (position, tiles, entities) in chunks {
+            self.next_ticket += 1;
+            self.in_flight
+                .insert(position, (self.next_ticket, entities.clone()));
+            writes.push(ChunkWrite {
+                position,
+                tiles,
+                entities,
+                ticket: self.next_ticket,
+            });
+        }
+
+        self.writer.write(writes);
+    }
+
+    pub fn read(&mut self, position: IVec2) -> Result<Option<ChunkEntities>, String> {
+        if let Some((_, entities)) = self.in_flight.get(&position) {
+            return Ok(Some(entities.clone()));
+        }
+
+        match self.reader.lock().unwrap().read(position)? {
+            Some(record) => record.entities().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// forgets the chunks that are safely on disk now
+    fn collect_written(&mut self) {
+        for (position, ticket) in self.writer.written() {
+            // a newer write of the same chunk is still on its way
+            if self.in_flight.get(&position).is_some_and(|(latest, _)| *latest == ticket) {
+                self.in_flight.remove(&position);
+            }
+        }
+    }
+}
+
+/// the stored trees and pigs of an unloaded chunk, ready to be written
+pub fn stored_chunk_entities(
+    position: IVec2,
+    play_time: &PlayTime,
+    tree_store: &TreeChunkStore,
+    pig_store: &PigChunkStore,
+) -> ChunkEntities {
+    let mut trees = Vec::new();
+    if let Some(chunk) = tree_store.chunks.get(&position) {
+        let unloaded_for = (play_time.0 - chunk.unloaded_at).max(0.);
+        for tree in chunk.trees.iter() {
+            let mut tree = tree.clone();
+            tree.advance(unloaded_for);
+            trees.push((&tree).into());
+        }
+    }
+
+    let pigs = pig_store
+        .chunks
+        .get(&position)
+        .map(|pigs| pigs.iter().map(|pig| pig.into()).collect())
+        .unwrap_or_default();
+
+    ChunkEntities {
+        version: CHUNK_VERSION,
+        unloaded_at: play_time.0,
+        trees,
+        pigs,
+    }
+}
+
+pub fn open_world_storage(
+    mut commands: Commands,
+    current_slot: Res<CurrentSlot>,
+    default_backend: Res<ChunkBackend>,
+) {
+    let backend = ChunkBackend::of_slot(&current_slot.0).unwrap_or(*default_backend);
+
+    match WorldStorage::open(backend, &current_slot.0) {
+        Ok(storage) => {
+            info!("chunks of {:?} are kept in {}", current_slot.0, backend.name());
+            commands.insert_resource(storage);
+        }
+        // the world still works, it just keeps every chunk in memory until it is saved
+        Err(e) => error!("can't open the chunk store: {}", e),
+    }
+}
+
+/// waits for the writer to finish, so the next world can open the same files
+pub fn close_world_storage(mut commands: Commands) {
+    commands.remove_resource::<WorldStorage>();
+}
+
+/// puts what is on disk for the new chunks into the tree and pig stores,
+/// right before they spawn their content
+pub fn fetch_stored_chunks(
+    play_time: Res<PlayTime>,
+    storage: Option<ResMut<WorldStorage>>,
+    mut tree_store: ResMut<TreeChunkStore>,
+    mut pig_store: ResMut<PigChunkStore>,
+    mut chunk_spawned: EventReader<ChunkSpawned>,
+) {
+    let Some(mut storage) = storage else {
+        chunk_spawned.clear();
+        return;
+    };
+
+    for event in chunk_spawned.read() {
+        if tree_store.chunks.contains_key(&event.position) {
+            continue;
+        }
+
+        let entities = match storage.read(event.position) {
+            Ok(Some(entities)) => entities,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("chunk {:?} is generated again: {}", event.position, e);
+                continue;
+            }
+        };
+
+        // a store from a world that was played longer would make trees younger
+        let unloaded_for = (play_time.0 - entities.unloaded_at).max(0.);
+        let trees = entities
+            .trees
+            .iter()
+            .map(|tree| {
+                let mut tree = TreeState::from(tree);
+                tree.advance(unloaded_for);
+                tree
+            })
+            .collect();
+
+        tree_store.chunks.insert(
+            event.position,
+            TreeChunk {
+                trees,
+                unloaded_at: play_time.0,
+            },
+        );
+        pig_store.chunks.insert(
+            event.position,
+            entities.pigs.iter().map(PigState::from).collect(),
+        );
+    }
+}
+
+/// hands the content of despawned chunks to the writer thread
+pub fn persist_unloaded_chunks(
+    play_time: Res<PlayTime>,
+    rng_jesus: Res<RngJesus>,
+    storage: Option<ResMut<WorldStorage>>,
+    mut tree_store: ResMut<TreeChunkStore>,
+    mut pig_store: ResMut<PigChunkStore>,
+    mut chunk_despawned: EventReader<ChunkDespawned>,
+) {
+    let Some(mut storage) = storage else {
+        chunk_despawned.clear();
+        return;
+    };
+    storage.collect_written();
+
+    let chunks: Vec<_> = chunk_despawned
+        .read()
+        .map(|event| {
+            let entities = stored_chunk_entities(event.position, &play_time, &tree_store, &pig_store);
+            tree_store.chunks.remove(&event.position);
+            pig_store.chunks.remove(&event.position);
+            (event.position, chunk_tiles(&rng_jesus, event.position), entities)
+        })
+        .collect();
+
+    storage.write(chunks);
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::{TileColor, TileTextureIndex};
+
+use super::{
+    daytime::WorldClock,
+    world_map::chunk_gen::{GroundTile, TileType},
+};
+use crate::game_state::GameState;
+
+pub const DAYS_PER_SEASON: u32 = 3;
+
+pub struct SeasonPlugin;
+
+impl Plugin for SeasonPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Season>()
+            .add_systems(
+                Update,
+                (track_season, dress_ground)
+                    .chain()
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .register_type::<Season>();
+    }
+}
+
+/// The season of the world right now, it follows from the day of the `WorldClock`.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// every world starts in spring, each season lasts `DAYS_PER_SEASON` days
+    pub fn of_day(day: u32) -> Season {
+        match (day.saturating_sub(1) / DAYS_PER_SEASON) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        }
+    }
+
+    /// how much faster trees grow in this season
+    pub fn growth_factor(&self) -> f32 {
+        match self {
+            Season::Spring => 1.5,
+            Season::Summer => 1.,
+            Season::Autumn => 0.75,
+            Season::Winter => 0.25,
+        }
+    }
+
+    /// how much faster pigs are ready for the next piglet, they don't breed in winter at all
+    pub fn breeding_factor(&self) -> f32 {
+        match self {
+            Season::Spring => 2.,
+            Season::Summer | Season::Autumn => 1.,
+            Season::Winter => 0.,
+        }
+    }
+
+    /// the texture and color a tile is drawn with in this season
+    pub fn ground_look(&self, tile: TileType) -> (TileType, Color) {
+        match (self, tile) {
+            (Season::Autumn, TileType::GrassLand) => (tile, Color::rgb(1., 0.7, 0.35)),
+            (Season::Winter, TileType::GrassLand) => (TileType::White, Color::WHITE),
+            // frozen over, in a light blue so it still reads as water
+            (Season::Winter, TileType::ShallowWater) => {
+                (TileType::White, Color::rgb(0.75, 0.9, 1.))
+            }
+            _ => (tile, Color::WHITE),
+        }
+    }
+}
+
+fn track_season(clock: Res<WorldClock>, mut season: ResMut<Season>) {
+    let current = clock.season();
+    if *season != current {
+        info!("{} begins", current.name());
+        *season = current;
+    }
+}
+
+/// redraws the ground when the season changes, and every new chunk in the current season
+fn dress_ground(
+    season: Res<Season>,
+    mut tiles: ParamSet<(
+        Query<(&GroundTile, &mut TileTextureIndex, &mut TileColor)>,
+        Query<(&GroundTile, &mut TileTextureIndex, &mut TileColor), Added<GroundTile>>,
+    )>,
+) {
+    if season.is_changed() {
+        for (ground, texture, color) in &mut tiles.p0() {
+            dress(*season, ground, texture, color);
+        }
+    } else {
+        for (ground, texture, color) in &mut tiles.p1() {
+            dress(*season, ground, texture, color);
+        }
+    }
+}
+
+fn dress(
+    season: Season,
+    ground: &GroundTile,
+    mut texture: Mut<TileTextureIndex>,
+    mut color: Mut<TileColor>,
+) {
+    let (look, tint) = season.ground_look(ground.0);
+    *texture = look.into();
+    *color = TileColor(tint);
+}
@@ -0,0 +1,312 @@
+use std::fs;
+
+use bevy::{
+    audio::{Volume, VolumeLevel},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_turborand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    data::{load_data, settings_path},
+    player::Player,
+};
+
+const AUDIO_SETTINGS_FILE: &str = "audio.ron";
+// sounds further away from the player than this can't be heard
+const HEARING_RANGE: f32 = 400.;
+// sound effects playing at once, more are dropped instead of piling up
+const MAX_SFX: usize = 16;
+// the same effect playing at once, ten trees falling together play at most three "yeah buddy"s
+const MAX_SAME_SFX: usize = 3;
+// each click on a volume button turns it up this much, past full it starts at silent again
+const VOLUME_STEP: f32 = 0.25;
+
+/// Plays every sound of the game. Sound effects are asked for with a `PlaySfx` event,
+/// loops like the weather ambience are spawned with `looping_sound` so the
+/// volume settings reach them too.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AudioSettings::load())
+            .insert_resource(SfxDefs::load())
+            .init_resource::<GlobalRng>()
+            .add_event::<PlaySfx>()
+            .add_systems(PostUpdate, (play_sfx, apply_audio_settings));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+    Ambient,
+}
+
+impl VolumeChannel {
+    pub const ALL: [VolumeChannel; 4] = [
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Sfx,
+        VolumeChannel::Ambient,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "Master",
+            VolumeChannel::Music => "Music",
+            VolumeChannel::Sfx => "Effects",
+            VolumeChannel::Ambient => "Ambience",
+        }
+    }
+}
+
+/// How loud every channel is, from 0 to 1, stored in `settings/audio.ron`.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub ambient: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: 1.,
+            sfx: 1.,
+            ambient: 1.,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// the audio settings of the player, the defaults if there are none or they are broken
+    pub fn load() -> Self {
+        let path = settings_path().join(AUDIO_SETTINGS_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        ron::from_str(&content).unwrap_or_else(|e| {
+            error!("can't parse {}, using the default volumes: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let folder = settings_path();
+        fs::create_dir_all(&folder)
+            .map_err(|e| format!("can't create {}: {}", folder.display(), e))?;
+
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("can't serialize audio settings: {}", e))?;
+        let path = folder.join(AUDIO_SETTINGS_FILE);
+        fs::write(&path, content).map_err(|e| format!("can't write {}: {}", path.display(), e))
+    }
+
+    /// the setting of `channel` on its own
+    pub fn get(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Music => self.music,
+            VolumeChannel::Sfx => self.sfx,
+            VolumeChannel::Ambient => self.ambient,
+        }
+    }
+
+    /// how loud `channel` really is, the master volume included
+    pub fn volume(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            _ => self.master * self.get(channel),
+        }
+    }
+
+    /// turns `channel` up a step, from full back to silent
+    pub fn step(&mut self, channel: VolumeChannel) {
+        let volume = match channel {
+            VolumeChannel::Master => &mut self.master,
+            VolumeChannel::Music => &mut self.music,
+            VolumeChannel::Sfx => &mut self.sfx,
+            VolumeChannel::Ambient => &mut self.ambient,
+        };
+        *volume = if *volume >= 1. {
+            0.
+        } else {
+            (*volume + VOLUME_STEP).min(1.)
+        };
+    }
+}
+
+/// Every sound effect, what they sound like is in `assets/data/sounds.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Sfx {
+    Chop,
+    TreeFalls,
+    Hit,
+    EnemyKilled,
+    PlayerDeath,
+    Oink,
+    PigGrabbed,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SfxDef {
+    // one of them is played at random
+    pub files: Vec<String>,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SfxDefs(pub HashMap<Sfx, SfxDef>);
+
+impl SfxDefs {
+    fn load() -> Self {
+        match load_data::<HashMap<Sfx, SfxDef>>("sounds.ron") {
+            Ok(defs) => Self(defs),
+            Err(e) => {
+                error!("no sound effects loaded, the game will be quiet: {}", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Asks for a sound effect. With a position it gets quieter the further it is from the player.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaySfx {
+    pub sfx: Sfx,
+    pub position: Option<Vec2>,
+}
+
+impl PlaySfx {
+    /// a sound that is just as loud anywhere
+    pub fn new(sfx: Sfx) -> Self {
+        Self {
+            sfx,
+            position: None,
+        }
+    }
+
+    pub fn at(sfx: Sfx, position: Vec2) -> Self {
+        Self {
+            sfx,
+            position: Some(position),
+        }
+    }
+}
+
+/// how loud something `distance` away from the player is, `None` if it can't be heard at all
+pub fn attenuation(distance: f32) -> Option<f32> {
+    if distance >= HEARING_RANGE {
+        return None;
+    }
+    Some(1. - distance / HEARING_RANGE)
+}
+
+/// The channel and own volume of a playing sound, so changed settings can reach it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Sound {
+    pub channel: VolumeChannel,
+    pub volume: f32,
+}
+
+impl Sound {
+    fn level(&self, settings: &AudioSettings) -> f32 {
+        self.volume * settings.volume(self.channel)
+    }
+}
+
+#[derive(Component)]
+struct PlayingSfx(Sfx);
+
+/// a sound that plays until it is despawned, at `volume` times the setting of `channel`
+pub fn looping_sound(
+    source: Handle<AudioSource>,
+    channel: VolumeChannel,
+    volume: f32,
+    settings: &AudioSettings,
+) -> (AudioBundle, Sound) {
+    let sound = Sound { channel, volume };
+    (
+        AudioBundle {
+            source,
+            settings: PlaybackSettings {
+                volume: Volume::Relative(VolumeLevel::new(sound.level(settings))),
+                ..PlaybackSettings::LOOP
+            },
+        },
+        sound,
+    )
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<AudioSettings>,
+    defs: Res<SfxDefs>,
+    mut global_rng: ResMut<GlobalRng>,
+    mut requests: EventReader<PlaySfx>,
+    player: Query<&Transform, With<Player>>,
+    playing: Query<&PlayingSfx>,
+) {
+    let player = player.get_single().ok().map(|player| player.translation.truncate());
+    let mut playing: Vec<Sfx> = playing.iter().map(|playing| playing.0).collect();
+
+    for request in requests.read() {
+        if playing.len() >= MAX_SFX
+            || playing.iter().filter(|sfx| **sfx == request.sfx).count() >= MAX_SAME_SFX
+        {
+            continue;
+        }
+        let Some(def) = defs.0.get(&request.sfx) else {
+            continue;
+        };
+        let Some(file) = global_rng.sample(&def.files) else {
+            continue;
+        };
+
+        let distance = match (request.position, player) {
+            (Some(position), Some(player)) => position.distance(player),
+            _ => 0.,
+        };
+        let Some(attenuation) = attenuation(distance) else {
+            continue;
+        };
+
+        let sound = Sound {
+            channel: VolumeChannel::Sfx,
+            volume: def.volume * attenuation,
+        };
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load(file),
+                settings: PlaybackSettings {
+                    volume: Volume::Relative(VolumeLevel::new(sound.level(&settings))),
+                    speed: 1. + global_rng.f32_normalized() * def.pitch,
+                    ..PlaybackSettings::DESPAWN
+                },
+            },
+            sound,
+            PlayingSfx(request.sfx),
+        ));
+        playing.push(request.sfx);
+    }
+}
+
+/// sounds that are already playing follow the volume settings as well
+fn apply_audio_settings(settings: Res<AudioSettings>, sounds: Query<(&Sound, &AudioSink)>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (sound, sink) in sounds.iter() {
+        sink.set_volume(sound.level(&settings));
+    }
+}
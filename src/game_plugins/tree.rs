@@ -1,19 +1,243 @@
-use super::player::Player;
+use super::{
+    animation::{Clip, SpriteAnimation},
+    console::{typed, Console, ConsoleApp, ConsoleCommand},
+    inventory::{Inventory, Item},
+    player::{InteractSet, Player, PlayerInteract},
+    save::PlayTime,
+    seasons::Season,
+    sound::{PlaySfx, Sfx},
+    weather::Weather,
+    world_map::{
+        chunk_gen::{BiomTiles, TileType},
+        utils::{tiles_to_chunks, tiles_to_world},
+        world_gen::{ChunkDespawned, ChunkManager, ChunkSet, ChunkSpawned, RngJesus},
+    },
+    y_sort::YSort,
+};
+use crate::{consts::CHUNK_SIZE, game_state::GameState};
 
-use bevy::{prelude::*, audio::VolumeLevel};
+use bevy::{prelude::*, utils::HashMap};
 use bevy_turborand::prelude::*;
+use serde::{Deserialize, Serialize};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-#[derive(Component, Reflect)]
-pub struct TreeParent;
+// how many spots per chunk are tried when a chunk gets generated for the first time
+const TREE_ATTEMPTS_PER_CHUNK: usize = 12;
+// hard cap so spreading seeds can never make the tree count explode again
+const MAX_TREES_PER_CHUNK: usize = 24;
+const TREE_HIT_RANGE: f32 = 20.;
+const TREE_HIT_DAMAGE: usize = 5;
+// keeps the trees of a chunk from being placed like its pigs
+const TREE_SEED_SALT: u64 = 0x7433;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum TreeStage {
+    Stump,
+    Sapling,
+    Young,
+    Mature,
+}
+
+impl TreeStage {
+    pub fn next(&self) -> Option<TreeStage> {
+        match self {
+            TreeStage::Stump => Some(TreeStage::Sapling),
+            TreeStage::Sapling => Some(TreeStage::Young),
+            TreeStage::Young => Some(TreeStage::Mature),
+            TreeStage::Mature => None,
+        }
+    }
+
+    /// wood dropped when a tree of this stage gets chopped down
+    pub fn wood(&self) -> u32 {
+        match self {
+            TreeStage::Stump => 0,
+            TreeStage::Sapling => 1,
+            TreeStage::Young => 2,
+            TreeStage::Mature => 4,
+        }
+    }
+
+    /// seconds it takes to get from this stage into the next one
+    pub fn growth_seconds(&self) -> f32 {
+        match self {
+            TreeStage::Stump => 60.,
+            TreeStage::Sapling => 90.,
+            TreeStage::Young => 120.,
+            TreeStage::Mature => 0.,
+        }
+    }
+
+    pub fn max_health(&self) -> usize {
+        match self {
+            TreeStage::Stump => 0,
+            TreeStage::Sapling => 5,
+            TreeStage::Young => 10,
+            TreeStage::Mature => 20,
+        }
+    }
+
+    pub fn texture(&self) -> &'static str {
+        match self {
+            TreeStage::Stump | TreeStage::Sapling => "images/tree.png",
+            TreeStage::Young | TreeStage::Mature => "images/tree-pine-v1.png",
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        match self {
+            TreeStage::Stump => 0.3,
+            TreeStage::Sapling => 0.4,
+            TreeStage::Young => 0.7,
+            TreeStage::Mature => 1.,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            // there is no stump sprite yet, so a darkened small tree has to do
+            TreeStage::Stump => Color::rgb(0.45, 0.3, 0.2),
+            _ => Color::WHITE,
+        }
+    }
+
+    fn apply(
+        &self,
+        asset_server: &AssetServer,
+        sprite: &mut Sprite,
+        transform: &mut Transform,
+        texture: &mut Handle<Image>,
+    ) {
+        sprite.color = self.color();
+        transform.scale = Vec3::new(self.scale(), self.scale(), 1.);
+        *texture = asset_server.load(self.texture());
+    }
+
+    fn sprite(&self, asset_server: &AssetServer, tile: IVec2) -> SpriteBundle {
+        let (x, y) = tiles_to_world(tile);
+
+        SpriteBundle {
+            texture: asset_server.load(self.texture()),
+            sprite: Sprite {
+                color: self.color(),
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3 { x, y, z: 0.0 },
+                scale: Vec3 {
+                    x: self.scale(),
+                    y: self.scale(),
+                    z: 1.,
+                },
+                ..default()
+            },
+            ..default()
+        }
+    }
+}
 
 #[derive(Component, Reflect)]
 pub struct Tree {
-    health: usize,
+    pub stage: TreeStage,
+    pub health: usize,
+    pub growth: Timer,
+    pub tile: IVec2,
+}
+
+impl Tree {
+    pub fn new(stage: TreeStage, tile: IVec2) -> Self {
+        Self {
+            stage,
+            health: stage.max_health(),
+            growth: Timer::from_seconds(stage.growth_seconds(), TimerMode::Once),
+            tile,
+        }
+    }
+
+    pub fn chunk(&self) -> IVec2 {
+        tiles_to_chunks(self.tile)
+    }
+
+    fn set_stage(&mut self, stage: TreeStage) {
+        *self = Tree::new(stage, self.tile);
+    }
+
+    pub fn to_state(&self) -> TreeState {
+        TreeState {
+            stage: self.stage,
+            health: self.health,
+            growth_elapsed: self.growth.elapsed_secs(),
+            tile: self.tile,
+        }
+    }
+}
+
+/// What is left of a tree once its chunk is not spawned anymore.
+#[derive(Debug, Clone, Reflect)]
+pub struct TreeState {
+    pub stage: TreeStage,
+    pub health: usize,
+    pub growth_elapsed: f32,
+    pub tile: IVec2,
 }
 
-impl Default for Tree {
+impl TreeState {
+    /// lets the tree keep growing for the time its chunk was unloaded
+    pub fn advance(&mut self, seconds: f32) {
+        // a chunk can't have been away for less than nothing, that would make it younger
+        let mut seconds = seconds.max(0.);
+        while let Some(next) = self.stage.next() {
+            let left = self.stage.growth_seconds() - self.growth_elapsed;
+            if seconds < left {
+                self.growth_elapsed += seconds;
+                return;
+            }
+            seconds -= left;
+            self.stage = next;
+            self.health = next.max_health();
+            self.growth_elapsed = 0.;
+        }
+    }
+
+    fn to_tree(&self) -> Tree {
+        let mut tree = Tree::new(self.stage, self.tile);
+        tree.health = self.health;
+        tree.growth
+            .set_elapsed(std::time::Duration::from_secs_f32(self.growth_elapsed.max(0.)));
+        tree
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TreeChunk {
+    pub trees: Vec<TreeState>,
+    // `PlayTime` when the chunk was unloaded
+    pub unloaded_at: f32,
+}
+
+/// Trees of every chunk that was visited but is not spawned right now.
+#[derive(Resource, Debug, Default)]
+pub struct TreeChunkStore {
+    pub chunks: HashMap<IVec2, TreeChunk>,
+}
+
+#[derive(Resource)]
+pub struct TreeSettings {
+    pub seed_spread: bool,
+    // chance per mature tree and second to drop a seed
+    pub seed_chance: f64,
+    // max distance in tiles a seed can fly
+    pub seed_range: i32,
+}
+
+impl Default for TreeSettings {
     fn default() -> Self {
-        return Self { health: 10 };
+        Self {
+            seed_spread: true,
+            seed_chance: 0.002,
+            seed_range: 3,
+        }
     }
 }
 
@@ -25,140 +249,280 @@ pub struct TreePlugin;
 impl Plugin for TreePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TreeCount>()
+            .init_resource::<TreeChunkStore>()
+            .init_resource::<TreeSettings>()
             .init_resource::<GlobalRng>()
-            // .add_systems(Startup, spawn_trees)
-            .add_systems(Update, tree_hit)
+            .add_systems(
+                Update,
+                (
+                    (spawn_chunk_trees, store_chunk_trees).in_set(ChunkSet::Content),
+                    tree_hit.in_set(InteractSet::Tree),
+                    tree_growth,
+                    spread_seeds,
+                    count_trees,
+                )
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(OnEnter(GameState::MENU), despawn_all_trees)
+            .add_console_command(
+                "trees grow",
+                "trees grow - every loaded tree grows to its next stage",
+                grow_trees_command,
+            )
             .register_type::<Tree>();
     }
 }
 
-fn spawn_trees(
+fn spawn_tree(commands: &mut Commands, asset_server: &AssetServer, tree: Tree) {
+    commands.spawn((
+        tree.stage.sprite(asset_server, tree.tile),
+        tree,
+        YSort,
+        Name::new("Tree"),
+    ));
+}
+
+/// picks the spots for the trees of a chunk that was never visited before.
+/// Seeded by the world seed, so the same world always gets the same forests.
+fn generate_chunk_trees(rng_jesus: &RngJesus, chunk_position: IVec2) -> Vec<TreeState> {
+    let mut rng = ChaCha8Rng::seed_from_u64(rng_jesus.chunk_seed(chunk_position, TREE_SEED_SALT));
+
+    let mut trees: Vec<TreeState> = Vec::new();
+
+    for _ in 0..TREE_ATTEMPTS_PER_CHUNK {
+        let tile = IVec2::new(
+            chunk_position.x * CHUNK_SIZE.x as i32 + rng.gen_range(0..CHUNK_SIZE.x as i32),
+            chunk_position.y * CHUNK_SIZE.y as i32 + rng.gen_range(0..CHUNK_SIZE.y as i32),
+        );
+        let stage = match rng.gen_range(0..10) {
+            0 => TreeStage::Sapling,
+            1..=2 => TreeStage::Young,
+            _ => TreeStage::Mature,
+        };
+
+        if rng_jesus.get_tile_type_at(&tile) != TileType::GrassLand
+            || trees.iter().any(|tree| tree.tile == tile)
+        {
+            continue;
+        }
+
+        trees.push(TreeState {
+            stage,
+            health: stage.max_health(),
+            growth_elapsed: 0.,
+            tile,
+        });
+    }
+
+    trees
+}
+
+fn spawn_chunk_trees(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut global_rng: ResMut<GlobalRng>,
+    rng_jesus: Res<RngJesus>,
+    play_time: Res<PlayTime>,
+    mut store: ResMut<TreeChunkStore>,
+    mut chunk_spawned: EventReader<ChunkSpawned>,
 ) {
-    commands
-        .spawn((
-            SpatialBundle::default(),
-            TreeParent,
-            RngComponent::from(&mut global_rng),
-            Name::new("Tree Parent"),
-        ))
-        .with_children(|commands| {
-            for _ in 0..20 {
-                let x = global_rng.isize(-600..600) as f32;
-                let y = global_rng.isize(-600..600) as f32;
-                let texture = asset_server.load("images/tree2.png");
-                commands.spawn((
-                    SpriteBundle {
-                        texture,
-                        transform: Transform {
-                            translation: Vec3 { x, y, z: 0.0 },
-                            scale: Vec3 {
-                                x: 0.05,
-                                y: 0.05,
-                                z: 0.,
-                            },
-                            ..default()
-                        },
-                        ..default()
-                    },
-                    Tree::default(),
-                    Name::new("Tree"),
-                ));
+    for event in chunk_spawned.read() {
+        let trees = match store.chunks.remove(&event.position) {
+            Some(mut chunk) => {
+                let unloaded_for = play_time.0 - chunk.unloaded_at;
+                for tree in chunk.trees.iter_mut() {
+                    tree.advance(unloaded_for);
+                }
+                chunk.trees
             }
-        });
+            None => generate_chunk_trees(&rng_jesus, event.position),
+        };
+
+        for tree in trees.iter() {
+            spawn_tree(&mut commands, &asset_server, tree.to_tree());
+        }
+    }
 }
 
-fn tree_hit(
+fn store_chunk_trees(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    player: Query<&Transform, With<Player>>,
-    mut tree_parent: Query<(Entity, &mut RngComponent), With<TreeParent>>,
-    mut trees: Query<(Entity, &Transform, &mut Tree), With<Tree>>,
-    input: Res<Input<KeyCode>>,
+    play_time: Res<PlayTime>,
+    mut store: ResMut<TreeChunkStore>,
+    mut chunk_despawned: EventReader<ChunkDespawned>,
+    trees: Query<(Entity, &Tree)>,
 ) {
+    for event in chunk_despawned.read() {
+        let mut chunk = TreeChunk {
+            trees: Vec::new(),
+            unloaded_at: play_time.0,
+        };
+
+        for (entity, tree) in trees.iter() {
+            if tree.chunk() == event.position {
+                chunk.trees.push(tree.to_state());
+                commands.entity(entity).despawn();
+            }
+        }
 
-    if !input.just_pressed(KeyCode::Space) {
-        return
+        store.chunks.insert(event.position, chunk);
     }
-    let (tree_parent, mut rng) = tree_parent.single_mut();
+}
 
-    let player = player.single();
+fn tree_growth(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    season: Res<Season>,
+    asset_server: Res<AssetServer>,
+    mut trees: Query<(&mut Tree, &mut Sprite, &mut Transform, &mut Handle<Image>)>,
+) {
+    for (mut tree, mut sprite, mut transform, mut texture) in &mut trees {
+        let Some(next) = tree.stage.next() else {
+            continue;
+        };
 
-    let mut player_cords_max = player.translation.clone();
-    let mut player_cords_min = player.translation.clone();
+        // rain and spring make everything grow faster, winter nearly stops it
+        let growth = weather.kind.growth_factor() * season.growth_factor();
+        tree.growth.tick(time.delta().mul_f32(growth));
+        if !tree.growth.finished() {
+            continue;
+        }
 
-    player_cords_max.x = player_cords_max.x + 20.;
-    player_cords_max.y = player_cords_max.y + 20.;
+        tree.set_stage(next);
+        next.apply(&asset_server, &mut sprite, &mut transform, &mut texture);
+    }
+}
+
+fn spread_seeds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<TreeSettings>,
+    rng_jesus: Res<RngJesus>,
+    chunk_manager: Res<ChunkManager>,
+    time: Res<Time>,
+    mut global_rng: ResMut<GlobalRng>,
+    trees: Query<&Tree>,
+) {
+    if !settings.seed_spread {
+        return;
+    }
+
+    let mut trees_per_chunk: HashMap<IVec2, usize> = HashMap::new();
+    for tree in trees.iter() {
+        *trees_per_chunk.entry(tree.chunk()).or_default() += 1;
+    }
 
-    player_cords_min.x = player_cords_min.x - 20.;
-    player_cords_min.y = player_cords_min.y - 20.;
+    let chance = (settings.seed_chance * time.delta_seconds() as f64).clamp(0., 1.);
 
-    let mut el_count: usize = 0;
-    let mut new_trees: usize = 0;
+    for tree in trees.iter() {
+        if tree.stage != TreeStage::Mature || !global_rng.chance(chance) {
+            continue;
+        }
 
-    for (entity, transform, mut tree) in &mut trees {
-        let diff = player.translation - transform.translation;
-        el_count += 1;
+        let range = settings.seed_range;
+        let tile = tree.tile
+            + IVec2::new(
+                global_rng.i32(-range..=range),
+                global_rng.i32(-range..=range),
+            );
+        let chunk = tiles_to_chunks(tile);
 
-        
-        if     diff.x <= 20.
-            && diff.x >= -20.
-            && diff.y <= 20.
-            && diff.y >= -20.
+        if !chunk_manager.contains(&chunk)
+            || trees_per_chunk.get(&chunk).copied().unwrap_or(0) >= MAX_TREES_PER_CHUNK
+            || rng_jesus.get_tile_type_at(&tile) != TileType::GrassLand
+            || trees.iter().any(|other| other.tile == tile)
         {
-            // println!("tree!tree!tree!tree!tree!tree!tree!tree!tree!tree!tree!");
-            let v = tree.health as isize;
-            commands.spawn(AudioBundle {
-                source: asset_server.load("sounds/Dump.ogg"),
-                settings: PlaybackSettings {
-                    volume: bevy::audio::Volume::Relative(VolumeLevel::new(0.05)),
-                    ..default()
-                }
-            });
-            if v -5 <= 0 {
-                commands.entity(tree_parent).remove_children(&[entity]).with_children(|commands| {
-                    for _ in 0..2 {
-                        let x = rng.isize(-2000..2000) as f32;
-                        let y = rng.isize(-2000..2000) as f32;
-                        let texture = asset_server.load("images/tree-pine-v1.png");
-                        commands.spawn((
-                            SpriteBundle {
-                                texture,
-                                transform: Transform {
-                                    translation: Vec3 { x, y, z: 0.0 },
-                                    scale: Vec3 {
-                                        x: 1.,
-                                        y: 1.,
-                                        z: 0.,
-                                    },
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            Tree::default(),
-                            Name::new("Tree"),
-                        ));
-                        new_trees += 1;
-                    }
-                });
-                commands.entity(entity).despawn();
-                commands.spawn(AudioBundle {
-                    source: asset_server.load("sounds/yeah_budy.ogg"),
-                    settings: PlaybackSettings {
-                        volume: bevy::audio::Volume::Relative(VolumeLevel::new(0.05)),
-                        ..default()
-                    }
-                });
-            } else {
-                tree.health -= 5;
-            }
-        } //else {println!("no tree here")}
+            continue;
+        }
+
+        *trees_per_chunk.entry(chunk).or_default() += 1;
+        spawn_tree(
+            &mut commands,
+            &asset_server,
+            Tree::new(TreeStage::Sapling, tile),
+        );
+        debug!("a seed took root at {:?}", tile);
+    }
+}
+
+/// finishes the growth timers, `tree_growth` does the rest
+fn grow_trees_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut trees: Query<&mut Tree>,
+) {
+    for _ in typed(&mut commands, "trees grow") {
+        for mut tree in &mut trees {
+            let remaining = tree.growth.remaining();
+            tree.growth.tick(remaining);
+        }
+        console.print(format!("growing {} trees", trees.iter().len()));
     }
+}
+
+fn despawn_all_trees(
+    mut commands: Commands,
+    mut store: ResMut<TreeChunkStore>,
+    trees: Query<Entity, With<Tree>>,
+) {
+    store.chunks.clear();
+    for entity in trees.iter() {
+        commands.entity(entity).despawn();
+    }
+}
 
-    if new_trees != 0 {
-        info!("spawen {} new trees. Total tree cound:{}", new_trees, el_count);
+fn count_trees(mut tree_count: ResMut<TreeCount>, trees: Query<&Tree>) {
+    tree_count.0 = trees.iter().count();
+}
+
+fn tree_hit(
+    asset_server: Res<AssetServer>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut player: Query<(&mut Inventory, &mut SpriteAnimation), With<Player>>,
+    mut trees: Query<(&mut Transform, &mut Tree, &mut Sprite, &mut Handle<Image>)>,
+    mut interact: ResMut<PlayerInteract>,
+) {
+    let Some(player_position) = interact.position() else {
+        return;
     };
 
+    // stumps have to regrow before they can be chopped again
+    let closest = trees
+        .iter_mut()
+        .filter(|(transform, tree, _, _)| {
+            let diff = player_position - transform.translation.truncate();
+            diff.x.abs() <= TREE_HIT_RANGE
+                && diff.y.abs() <= TREE_HIT_RANGE
+                && tree.stage != TreeStage::Stump
+        })
+        .min_by(|(a, ..), (b, ..)| {
+            let a = a.translation.truncate().distance(player_position);
+            let b = b.translation.truncate().distance(player_position);
+            a.total_cmp(&b)
+        });
+
+    let Some((mut transform, mut tree, mut sprite, mut texture)) = closest else {
+        return;
+    };
+    interact.consume();
+
+    let (mut inventory, mut animation) = player.single_mut();
+    animation.play(Clip::Chop);
+    // an axe cuts twice as deep as bare hands
+    let damage = if inventory.has(Item::Axe) {
+        TREE_HIT_DAMAGE * 2
+    } else {
+        TREE_HIT_DAMAGE
+    };
+
+    let position = transform.translation.truncate();
+    sfx.send(PlaySfx::at(Sfx::Chop, position));
+
+    if tree.health <= damage {
+        inventory.add(Item::Wood, tree.stage.wood());
+        tree.set_stage(TreeStage::Stump);
+        TreeStage::Stump.apply(&asset_server, &mut sprite, &mut transform, &mut texture);
+        sfx.send(PlaySfx::at(Sfx::TreeFalls, position));
+        info!("chopped down the tree at {:?}", tree.tile);
+    } else {
+        tree.health -= damage;
+    }
 }
@@ -0,0 +1,443 @@
+use bevy::prelude::*;
+use bevy_turborand::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    combat::Health,
+    daytime::WorldClock,
+    player::Player,
+    sound::{looping_sound, AudioSettings, VolumeChannel},
+    world_map::{
+        chunk_gen::{BiomTiles, TileType},
+        utils::tile_at_world_pos,
+        world_gen::RngJesus,
+    },
+};
+use crate::{game_state::GameState, zoom::WorldView};
+
+// in-game hours one kind of weather lasts at least
+const WEATHER_HOURS: u64 = 3;
+// keeps the weather from following the same pattern as the trees of the world
+const WEATHER_SALT: u64 = 0x57ea_7e52;
+// how fast the screen tint follows a change of weather, per second
+const TINT_SPEED: f32 = 0.5;
+const MAX_DROPS: usize = 1500;
+const DROP_SPEED: f32 = 520.;
+// chance per second of a lightning flash during a storm
+const LIGHTNING_CHANCE: f64 = 0.08;
+const STORM_WAVE_INTERVAL: f32 = 2.;
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .init_resource::<GlobalRng>()
+            .add_systems(Startup, setup_tint)
+            .add_systems(
+                OnEnter(GameState::MENU),
+                (reset_weather, hide_tint, stop_weather_effects),
+            )
+            .add_systems(OnEnter(GameState::PAUSED), pause_ambience)
+            .add_systems(OnExit(GameState::PAUSED), resume_ambience)
+            .add_systems(
+                Update,
+                (
+                    update_weather,
+                    (update_tint, spawn_drops, move_drops, play_ambience, storm_waves),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .register_type::<Weather>();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum WeatherKind {
+    Clear,
+    Cloudy,
+    Rain,
+    Storm,
+}
+
+impl WeatherKind {
+    /// what the weather can turn into, with how likely each one is
+    fn transitions(&self) -> &'static [(WeatherKind, u32)] {
+        match self {
+            WeatherKind::Clear => &[(WeatherKind::Clear, 6), (WeatherKind::Cloudy, 3)],
+            WeatherKind::Cloudy => &[
+                (WeatherKind::Clear, 3),
+                (WeatherKind::Cloudy, 3),
+                (WeatherKind::Rain, 3),
+            ],
+            WeatherKind::Rain => &[
+                (WeatherKind::Cloudy, 3),
+                (WeatherKind::Rain, 3),
+                (WeatherKind::Storm, 2),
+            ],
+            WeatherKind::Storm => &[(WeatherKind::Rain, 3), (WeatherKind::Storm, 1)],
+        }
+    }
+
+    fn next(&self, rng: &mut ChaCha8Rng) -> WeatherKind {
+        let transitions = self.transitions();
+        let total: u32 = transitions.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.gen_range(0..total);
+        for (kind, weight) in transitions {
+            if roll < *weight {
+                return *kind;
+            }
+            roll -= weight;
+        }
+
+        *self
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "Clear",
+            WeatherKind::Cloudy => "Cloudy",
+            WeatherKind::Rain => "Rain",
+            WeatherKind::Storm => "Storm",
+        }
+    }
+
+    fn tint(&self) -> Color {
+        match self {
+            WeatherKind::Clear => Color::rgba(0.5, 0.5, 0.55, 0.),
+            WeatherKind::Cloudy => Color::rgba(0.5, 0.5, 0.55, 0.15),
+            WeatherKind::Rain => Color::rgba(0.3, 0.35, 0.45, 0.25),
+            WeatherKind::Storm => Color::rgba(0.15, 0.17, 0.25, 0.4),
+        }
+    }
+
+    fn drops_per_second(&self) -> f32 {
+        match self {
+            WeatherKind::Clear | WeatherKind::Cloudy => 0.,
+            WeatherKind::Rain => 150.,
+            WeatherKind::Storm => 400.,
+        }
+    }
+
+    // sideways speed of the drops
+    fn wind(&self) -> f32 {
+        match self {
+            WeatherKind::Storm => -180.,
+            _ => -30.,
+        }
+    }
+
+    fn ambience(&self) -> Option<&'static str> {
+        match self {
+            WeatherKind::Clear | WeatherKind::Cloudy => None,
+            WeatherKind::Rain => Some("sounds/rain.wav"),
+            WeatherKind::Storm => Some("sounds/storm.wav"),
+        }
+    }
+
+    /// how much faster trees grow in this weather
+    pub fn growth_factor(&self) -> f32 {
+        match self {
+            WeatherKind::Rain => 2.,
+            WeatherKind::Storm => 1.5,
+            _ => 1.,
+        }
+    }
+
+    /// damage the waves do every few seconds to someone in deep water
+    pub fn deep_water_damage(&self) -> u32 {
+        match self {
+            WeatherKind::Storm => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// The weather right now. It follows from the world seed and the clock alone,
+/// so a loaded world has the same weather it had when it was saved.
+#[derive(Resource, Debug, Clone, Reflect)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    // counted in `WEATHER_HOURS` since the first day started, `None` until the
+    // weather was worked out for the current world
+    slot: Option<u64>,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::Clear,
+            slot: None,
+        }
+    }
+}
+
+fn weather_slot(clock: &WorldClock) -> u64 {
+    let hours = clock.day.saturating_sub(1) as f32 * 24. + clock.hour;
+    hours as u64 / WEATHER_HOURS
+}
+
+fn slot_rng(rng_jesus: &RngJesus, slot: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(((rng_jesus.seed as u64) << 32) ^ WEATHER_SALT ^ slot)
+}
+
+impl Weather {
+    /// every world starts clear and walks the same chain of weathers from there
+    pub fn at(rng_jesus: &RngJesus, slot: u64) -> Self {
+        let mut weather = Weather {
+            kind: WeatherKind::Clear,
+            slot: Some(0),
+        };
+        while weather.slot != Some(slot) {
+            weather.step(rng_jesus);
+        }
+        weather
+    }
+
+    fn step(&mut self, rng_jesus: &RngJesus) {
+        let slot = self.slot.map_or(0, |slot| slot + 1);
+        self.kind = self.kind.next(&mut slot_rng(rng_jesus, slot));
+        self.slot = Some(slot);
+    }
+}
+
+/// Full screen node between the daylight tint and the rest of the UI.
+#[derive(Component, Default)]
+struct WeatherTint {
+    // eases towards the tint of the current weather
+    color: [f32; 4],
+    flash: f32,
+}
+
+#[derive(Component)]
+struct RainDrop {
+    velocity: Vec2,
+    life: Timer,
+}
+
+#[derive(Component)]
+struct WeatherAmbience(&'static str);
+
+fn setup_tint(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            background_color: BackgroundColor(Color::NONE),
+            z_index: ZIndex::Global(i32::MIN + 1),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            ..default()
+        },
+        WeatherTint::default(),
+        Name::new("Weather Tint"),
+    ));
+}
+
+fn reset_weather(mut weather: ResMut<Weather>) {
+    *weather = Weather::default();
+}
+
+fn hide_tint(mut tint: Query<(&mut BackgroundColor, &mut WeatherTint)>) {
+    for (mut color, mut tint) in &mut tint {
+        *color = BackgroundColor(Color::NONE);
+        *tint = WeatherTint::default();
+    }
+}
+
+fn stop_weather_effects(
+    mut commands: Commands,
+    effects: Query<Entity, Or<(With<RainDrop>, With<WeatherAmbience>)>>,
+) {
+    for entity in effects.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_weather(rng_jesus: Res<RngJesus>, clock: Res<WorldClock>, mut weather: ResMut<Weather>) {
+    let slot = weather_slot(&clock);
+    if weather.slot == Some(slot) {
+        return;
+    }
+
+    let before = weather.kind;
+    match weather.slot {
+        Some(current) if current + 1 == slot => weather.step(&rng_jesus),
+        // a new or loaded world, or the clock jumped
+        _ => *weather = Weather::at(&rng_jesus, slot),
+    }
+
+    if weather.kind != before {
+        info!("the weather changes to {}", weather.kind.name());
+    }
+}
+
+fn update_tint(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    mut global_rng: ResMut<GlobalRng>,
+    mut tint: Query<(&mut BackgroundColor, &mut WeatherTint)>,
+) {
+    let chance = (LIGHTNING_CHANCE * time.delta_seconds() as f64).clamp(0., 1.);
+    let lightning = weather.kind == WeatherKind::Storm && global_rng.chance(chance);
+    let step = (TINT_SPEED * time.delta_seconds()).min(1.);
+    let target = weather.kind.tint().as_rgba_f32();
+
+    for (mut color, mut tint) in &mut tint {
+        if lightning {
+            tint.flash = 1.;
+        }
+        tint.flash = (tint.flash - time.delta_seconds() * 4.).max(0.);
+        for (channel, target) in tint.color.iter_mut().zip(target) {
+            *channel += (target - *channel) * step;
+        }
+
+        // a lightning flash lights everything up for a moment
+        let flash = tint.flash * 0.6;
+        let [r, g, b, a] = tint.color;
+        *color = BackgroundColor(Color::rgba(
+            r + (1. - r) * flash,
+            g + (1. - g) * flash,
+            b + (1. - b) * flash,
+            a.max(flash),
+        ));
+    }
+}
+
+/// rains over whatever part of the world the camera looks at
+fn spawn_drops(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    mut global_rng: ResMut<GlobalRng>,
+    mut carry: Local<f32>,
+    camera: Query<(&Transform, &OrthographicProjection), With<WorldView>>,
+    drops: Query<(), With<RainDrop>>,
+) {
+    *carry += weather.kind.drops_per_second() * time.delta_seconds();
+    let count = *carry as usize;
+    *carry -= count as f32;
+    if count == 0 {
+        return;
+    }
+
+    let (camera, projection) = camera.single();
+    let center = camera.translation.truncate();
+    let half_size = projection.area.half_size();
+    // drops keep the same size on screen however far the camera is zoomed out
+    let scale = half_size.y / 72.;
+    let velocity = Vec2::new(weather.kind.wind(), -DROP_SPEED) * scale;
+
+    for _ in 0..count.min(MAX_DROPS.saturating_sub(drops.iter().count())) {
+        let offset = Vec2::new(
+            (global_rng.f32() * 2. - 1.) * half_size.x,
+            (global_rng.f32() * 2. - 1.) * half_size.y,
+        );
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.7, 0.8, 1., 0.5),
+                    custom_size: Some(Vec2::new(0.6, 5.) * scale),
+                    ..default()
+                },
+                transform: Transform::from_translation((center + offset).extend(10.))
+                    .with_rotation(Quat::from_rotation_z(-velocity.x.atan2(-velocity.y))),
+                ..default()
+            },
+            RainDrop {
+                velocity,
+                life: Timer::from_seconds(0.3 + global_rng.f32() * 0.4, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn move_drops(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut drops: Query<(Entity, &mut Transform, &mut RainDrop)>,
+) {
+    for (entity, mut transform, mut drop) in &mut drops {
+        if drop.life.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (drop.velocity * time.delta_seconds()).extend(0.);
+    }
+}
+
+/// one looping sound for the current weather, swapped when the weather changes
+fn play_ambience(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    audio_settings: Res<AudioSettings>,
+    weather: Res<Weather>,
+    playing: Query<(Entity, &WeatherAmbience)>,
+) {
+    if !weather.is_changed() {
+        return;
+    }
+
+    let sound = weather.kind.ambience();
+    for (entity, ambience) in playing.iter() {
+        if Some(ambience.0) == sound {
+            return;
+        }
+        commands.entity(entity).despawn();
+    }
+
+    let Some(sound) = sound else {
+        return;
+    };
+    commands.spawn((
+        looping_sound(
+            asset_server.load(sound),
+            VolumeChannel::Ambient,
+            0.1,
+            &audio_settings,
+        ),
+        WeatherAmbience(sound),
+        Name::new("Weather Ambience"),
+    ));
+}
+
+fn pause_ambience(ambience: Query<&AudioSink, With<WeatherAmbience>>) {
+    for sink in ambience.iter() {
+        sink.pause();
+    }
+}
+
+fn resume_ambience(ambience: Query<&AudioSink, With<WeatherAmbience>>) {
+    for sink in ambience.iter() {
+        sink.play();
+    }
+}
+
+/// in a storm the waves on deep water wear the player down
+fn storm_waves(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    rng_jesus: Res<RngJesus>,
+    mut waves: Local<Option<Timer>>,
+    mut player: Query<(&Transform, &mut Health), With<Player>>,
+) {
+    let (transform, mut health) = player.single_mut();
+    let tile = tile_at_world_pos(transform.translation.truncate());
+    let damage = weather.kind.deep_water_damage();
+    if damage == 0 || rng_jesus.get_tile_type_at(&tile) != TileType::DeepWater {
+        *waves = None;
+        return;
+    }
+
+    let waves = waves.get_or_insert_with(|| {
+        Timer::from_seconds(STORM_WAVE_INTERVAL, TimerMode::Repeating)
+    });
+    if waves.tick(time.delta()).just_finished() {
+        health.damage(damage);
+        debug!("the storm waves hit the player for {}", damage);
+    }
+}
@@ -5,13 +5,15 @@ use bevy_ecs_tilemap::{
     TilemapBundle,
 };
 use noise::{NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
 use strum::EnumCount;
 use strum_macros::EnumCount as EnumCountMacro;
 
+use crate::game_plugins::seasons::Season;
 use crate::consts::{CHUNK_SIZE, NOISE_SCALE, TILE_PIXEL_SIZE, TILE_SIZE};
 
 use super::{
-    utils::{chunks_to_world, world_to_tiles},
+    utils::{chunks_to_world, tiles_to_chunks},
     world_gen::{RngJesus, TileMap, Chunk},
 };
 
@@ -55,7 +57,7 @@ impl From<TileType> for TileTextureIndex {
     }
 }
 
-#[derive(Debug, EnumCountMacro)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumCountMacro)]
 pub enum TileType {
     GrassLand,
     Beach,
@@ -64,6 +66,50 @@ pub enum TileType {
     White,
 }
 
+impl TileType {
+    /// can a creature walking on land stand on this tile
+    pub fn is_walkable(&self) -> bool {
+        match self {
+            TileType::GrassLand | TileType::Beach | TileType::White => true,
+            TileType::ShallowWater | TileType::DeepWater => false,
+        }
+    }
+
+    /// like `is_walkable`, but shallow water freezes over in winter
+    pub fn is_walkable_in(&self, season: Season) -> bool {
+        match self {
+            TileType::ShallowWater => season == Season::Winter,
+            _ => self.is_walkable(),
+        }
+    }
+
+    /// the byte a tile is stored as on disk. Never reuse an id once it was written.
+    pub fn id(&self) -> u8 {
+        match self {
+            TileType::GrassLand => 0,
+            TileType::Beach => 1,
+            TileType::ShallowWater => 2,
+            TileType::DeepWater => 3,
+            TileType::White => 4,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<TileType> {
+        match id {
+            0 => Some(TileType::GrassLand),
+            1 => Some(TileType::Beach),
+            2 => Some(TileType::ShallowWater),
+            3 => Some(TileType::DeepWater),
+            4 => Some(TileType::White),
+            _ => None,
+        }
+    }
+}
+
+/// What a tile of the tilemap was generated as, the season only changes how it looks.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GroundTile(pub TileType);
+
 struct Wrapper<T>(Vec<T>);
 impl<T> From<Wrapper<T>> for Vec<T> {
     fn from(w: Wrapper<T>) -> Vec<T> {
@@ -71,7 +117,7 @@ impl<T> From<Wrapper<T>> for Vec<T> {
     }
 }
 
-#[derive(Debug, EnumCountMacro)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumCountMacro, Serialize, Deserialize)]
 pub enum BiomType {
     // Moutains,
     GrassLand,
@@ -82,6 +128,13 @@ pub enum BiomType {
 pub trait BiomTiles {
     fn get_biom(&self, chunk_position: &IVec2) -> BiomType;
     fn get_tile_type(&self, biom: &BiomType, ttile_pos: &IVec2) -> TileType;
+
+    /// Looks up the tile type for a global tile position, without needing the chunk it lives in.
+    fn get_tile_type_at(&self, tile_pos: &IVec2) -> TileType {
+        let biom = self.get_biom(&tiles_to_chunks(*tile_pos));
+
+        self.get_tile_type(&biom, tile_pos)
+    }
 }
 
 impl BiomTiles for RngJesus {
@@ -247,6 +300,24 @@ impl BiomTiles for RngJesus {
     }
 }
 
+/// the tiles of a chunk, column by column like `spawn_chunks` places them
+pub fn chunk_tiles(rng_jesus: &RngJesus, position: IVec2) -> Vec<TileType> {
+    let biom = rng_jesus.get_biom(&position);
+    let mut tiles = Vec::with_capacity((CHUNK_SIZE.x * CHUNK_SIZE.y) as usize);
+
+    for x in 0..CHUNK_SIZE.x as i32 {
+        for y in 0..CHUNK_SIZE.y as i32 {
+            let tile = IVec2::new(
+                position.x * CHUNK_SIZE.x as i32 + x,
+                position.y * CHUNK_SIZE.y as i32 + y,
+            );
+            tiles.push(rng_jesus.get_tile_type(&biom, &tile));
+        }
+    }
+
+    tiles
+}
+
 pub fn spawn_chunks(
     commands: &mut Commands,
     asset_server: &AssetServer,
@@ -261,28 +332,24 @@ pub fn spawn_chunks(
     let image_handles = ImageHandles::load(asset_server);
     let texture_vec = TilemapTexture::Vector(image_handles.assets.into());
 
-    let biom = rng_jesus.get_biom(&chunk_position);
+    let tiles = chunk_tiles(rng_jesus, chunk_position);
 
     for x in 0..CHUNK_SIZE.x {
         for y in 0..CHUNK_SIZE.y {
             let tile_pos = TilePos { x, y };
-
-            let (tile_x, tile_y) = world_to_tiles(chunks_to_world(chunk_position, tile_pos));
-            let global_tile_pos: IVec2 = IVec2 {
-                x: tile_x,
-                y: tile_y,
-            };
-
-            let texture_index = rng_jesus.get_tile_type(&biom, &global_tile_pos);
+            let texture_index = tiles[(x * CHUNK_SIZE.y + y) as usize];
 
             let tile_entity = commands
-                .spawn(TileBundle {
-                    position: tile_pos,
-                    tilemap_id: TilemapId(tilemap_entity),
-                    texture_index: texture_index.into(),
-                    // color: color,
-                    ..Default::default()
-                })
+                .spawn((
+                    TileBundle {
+                        position: tile_pos,
+                        tilemap_id: TilemapId(tilemap_entity),
+                        texture_index: texture_index.into(),
+                        // color: color,
+                        ..Default::default()
+                    },
+                    GroundTile(texture_index),
+                ))
                 .id();
             commands.entity(tilemap_entity).add_child(tile_entity);
             tile_storage.set(&tile_pos, tile_entity);
@@ -0,0 +1,253 @@
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::FocusPolicy,
+    window::PrimaryWindow,
+};
+
+use super::{
+    minimap::{
+        new_map_image, paint_map, paint_marker, pixel_tile, tile_pixel, MapTiles, PLAYER_COLOR,
+        WAYPOINT_COLOR,
+    },
+    utils::tile_at_world_pos,
+};
+use crate::{
+    game_plugins::{
+        actions::{Action, ActionState},
+        player::Player,
+    },
+    game_state::GameState,
+};
+
+// the map is painted at half the window size and scaled up, so one pixel is two on screen
+const MAP_PIXELS: UVec2 = UVec2 { x: 640, y: 310 };
+// tiles per pixel, the mouse wheel moves between them
+const MAP_ZOOM_LEVELS: [i32; 5] = [1, 2, 4, 8, 16];
+// how close to a waypoint a right click has to be to remove it again, in map pixels
+const WAYPOINT_PICK_RADIUS: i32 = 4;
+
+pub struct MapScreenPlugin;
+
+impl Plugin for MapScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapScreen>()
+            .init_resource::<Waypoints>()
+            .add_systems(Startup, setup_map_screen)
+            .add_systems(OnEnter(GameState::MENU), (close_map, forget_waypoints))
+            // the map must not stay on top of the pause menu
+            .add_systems(OnEnter(GameState::PAUSED), close_map)
+            .add_systems(
+                Update,
+                (toggle_map, pan_and_zoom_map, place_waypoints, draw_map)
+                    .chain()
+                    .run_if(in_state(GameState::GAME)),
+            );
+    }
+}
+
+/// Whether the full-screen map is open and which part of the world it shows.
+#[derive(Resource, Debug)]
+pub struct MapScreen {
+    pub open: bool,
+    // the tile in the middle of the map, not rounded so slow drags still pan
+    center: Vec2,
+    zoom_level: usize,
+}
+
+impl Default for MapScreen {
+    fn default() -> Self {
+        Self {
+            open: false,
+            center: Vec2::ZERO,
+            zoom_level: 2,
+        }
+    }
+}
+
+impl MapScreen {
+    fn center_tile(&self) -> IVec2 {
+        self.center.round().as_ivec2()
+    }
+
+    fn scale(&self) -> i32 {
+        MAP_ZOOM_LEVELS[self.zoom_level]
+    }
+}
+
+/// run condition for everything that reads the mouse or keys the map uses itself
+pub fn map_closed(map: Res<MapScreen>) -> bool {
+    !map.open
+}
+
+/// Tiles the player marked on the map, they show up on the minimap as well.
+#[derive(Resource, Debug, Default)]
+pub struct Waypoints(pub Vec<IVec2>);
+
+#[derive(Component)]
+struct MapScreenImage(Handle<Image>);
+
+fn setup_map_screen(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(new_map_image(MAP_PIXELS.x, MAP_PIXELS.y));
+
+    commands.spawn((
+        ImageBundle {
+            image: UiImage::new(image.clone()),
+            // above the minimap, below the HUD
+            z_index: ZIndex::Global(i32::MAX - 1),
+            visibility: Visibility::Hidden,
+            // clicks on the map must not reach the minimap underneath
+            focus_policy: FocusPolicy::Block,
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            ..default()
+        },
+        MapScreenImage(image),
+        Name::new("Map Screen"),
+    ));
+}
+
+fn close_map(mut map: ResMut<MapScreen>, mut screen: Query<&mut Visibility, With<MapScreenImage>>) {
+    map.open = false;
+    *screen.single_mut() = Visibility::Hidden;
+}
+
+fn forget_waypoints(mut waypoints: ResMut<Waypoints>) {
+    waypoints.0.clear();
+}
+
+/// the map action (M) opens the map on the player and closes it again
+fn toggle_map(
+    actions: Res<ActionState>,
+    mut map: ResMut<MapScreen>,
+    player: Query<&Transform, With<Player>>,
+    mut screen: Query<&mut Visibility, With<MapScreenImage>>,
+) {
+    if !actions.just_pressed(Action::ToggleMap) {
+        return;
+    }
+
+    map.open = !map.open;
+    if map.open {
+        map.center = tile_at_world_pos(player.single().translation.truncate()).as_vec2();
+        *screen.single_mut() = Visibility::Visible;
+    } else {
+        *screen.single_mut() = Visibility::Hidden;
+    }
+}
+
+/// dragging with the left mouse button pans, the wheel zooms
+fn pan_and_zoom_map(
+    mut map: ResMut<MapScreen>,
+    buttons: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut scroll: EventReader<MouseWheel>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !map.open {
+        motion.clear();
+        scroll.clear();
+        return;
+    }
+
+    if buttons.pressed(MouseButton::Left) {
+        let pixels_per_screen = MAP_PIXELS.x as f32 / window.single().width();
+        let scale = map.scale() as f32;
+        for ev in motion.read() {
+            // the world moves with the mouse, so the center moves the other way
+            map.center += Vec2::new(-ev.delta.x, ev.delta.y) * pixels_per_screen * scale;
+        }
+    } else {
+        motion.clear();
+    }
+
+    let steps: f32 = scroll
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 40.,
+        })
+        .sum();
+    if steps > 0. && map.zoom_level > 0 {
+        map.zoom_level -= 1;
+    } else if steps < 0. && map.zoom_level < MAP_ZOOM_LEVELS.len() - 1 {
+        map.zoom_level += 1;
+    }
+}
+
+/// the map pixel under the cursor
+fn cursor_pixel(window: &Window) -> Option<IVec2> {
+    let cursor = window.cursor_position()?;
+    let pixel = cursor / Vec2::new(window.width(), window.height()) * MAP_PIXELS.as_vec2();
+    Some(pixel.floor().as_ivec2())
+}
+
+/// a right click puts a waypoint under the cursor, or takes away the one that is there
+fn place_waypoints(
+    map: Res<MapScreen>,
+    buttons: Res<Input<MouseButton>>,
+    mut waypoints: ResMut<Waypoints>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !map.open || !buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let Some(pixel) = cursor_pixel(window.single()) else {
+        return;
+    };
+
+    let (center, scale, size) = (map.center_tile(), map.scale(), MAP_PIXELS.as_ivec2());
+    let picked = waypoints.0.iter().position(|waypoint| {
+        let at = tile_pixel(center, scale, size, *waypoint);
+        (at - pixel).abs().max_element() <= WAYPOINT_PICK_RADIUS
+    });
+
+    match picked {
+        Some(index) => {
+            let waypoint = waypoints.0.remove(index);
+            info!("removed waypoint at {:?}", waypoint);
+        }
+        None => {
+            let waypoint = pixel_tile(center, scale, size, pixel);
+            info!("placed waypoint at {:?}", waypoint);
+            waypoints.0.push(waypoint);
+        }
+    }
+}
+
+/// unexplored tiles stay dark, that is all the fog of war there is
+fn draw_map(
+    map: Res<MapScreen>,
+    map_tiles: Res<MapTiles>,
+    waypoints: Res<Waypoints>,
+    mut images: ResMut<Assets<Image>>,
+    mut drawn: Local<Option<(IVec2, usize, IVec2)>>,
+    player: Query<&Transform, With<Player>>,
+    screen: Query<&MapScreenImage>,
+) {
+    if !map.open {
+        *drawn = None;
+        return;
+    }
+
+    let player = tile_at_world_pos(player.single().translation.truncate());
+    let state = (map.center_tile(), map.zoom_level, player);
+    if *drawn == Some(state) && !map_tiles.is_changed() && !waypoints.is_changed() {
+        return;
+    }
+    *drawn = Some(state);
+
+    let Some(image) = images.get_mut(&screen.single().0) else {
+        return;
+    };
+    let (center, scale, size) = (map.center_tile(), map.scale(), MAP_PIXELS.as_ivec2());
+    paint_map(image, &map_tiles, center, scale);
+    for waypoint in waypoints.0.iter() {
+        paint_marker(image, tile_pixel(center, scale, size, *waypoint), 2, WAYPOINT_COLOR);
+    }
+    paint_marker(image, tile_pixel(center, scale, size, player), 2, PLAYER_COLOR);
+}
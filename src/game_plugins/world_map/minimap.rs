@@ -0,0 +1,280 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    utils::{HashMap, HashSet},
+};
+use bevy_ecs_tilemap::{map::TilemapId, tiles::TilePos};
+
+use super::{
+    chunk_gen::{chunk_tiles, GroundTile, TileType},
+    map_screen::Waypoints,
+    utils::{tile_at_world_pos, tiles_to_chunks},
+    world_gen::{ChunkManager, RngJesus},
+};
+use crate::{consts::CHUNK_SIZE, game_plugins::player::Player, game_state::GameState};
+
+// width and height of the minimap in pixels
+const MINIMAP_PIXELS: u32 = 160;
+const MINIMAP_MARKER: f32 = 4.;
+// tiles per pixel, clicking the minimap goes to the next one
+const MINIMAP_ZOOM_LEVELS: [i32; 3] = [1, 2, 4];
+const UNEXPLORED_COLOR: [u8; 4] = [16, 16, 20, 255];
+// the same red as the marker in the middle of the minimap
+pub const PLAYER_COLOR: [u8; 4] = [255, 0, 0, 255];
+pub const WAYPOINT_COLOR: [u8; 4] = [250, 210, 40, 255];
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExploredChunks>()
+            .init_resource::<MapTiles>()
+            .add_systems(Startup, setup_minimap)
+            .add_systems(OnEnter(GameState::MENU), forget_map)
+            .add_systems(
+                Update,
+                (fill_explored_tiles, learn_spawned_tiles, minimap_zoom, draw_minimap)
+                    .chain()
+                    .run_if(in_state(GameState::GAME)),
+            );
+    }
+}
+
+/// Every chunk the player has been close enough to see.
+#[derive(Resource, Debug, Default)]
+pub struct ExploredChunks(pub HashSet<IVec2>);
+
+/// The tiles of the explored chunks, column by column like `chunk_tiles` returns them.
+#[derive(Resource, Debug, Default)]
+pub struct MapTiles {
+    chunks: HashMap<IVec2, Vec<TileType>>,
+}
+
+impl MapTiles {
+    pub fn insert(&mut self, chunk: IVec2, tiles: Vec<TileType>) {
+        self.chunks.insert(chunk, tiles);
+    }
+
+    pub fn tile(&self, tile: IVec2) -> Option<TileType> {
+        let chunk = tiles_to_chunks(tile);
+        let x = tile.x.rem_euclid(CHUNK_SIZE.x as i32);
+        let y = tile.y.rem_euclid(CHUNK_SIZE.y as i32);
+
+        self.chunks
+            .get(&chunk)
+            .map(|tiles| tiles[(x * CHUNK_SIZE.y as i32 + y) as usize])
+    }
+}
+
+/// the color a tile has on the maps
+pub fn tile_color(tile: TileType) -> [u8; 4] {
+    match tile {
+        TileType::GrassLand => [76, 154, 63, 255],
+        TileType::Beach => [222, 203, 142, 255],
+        TileType::ShallowWater => [82, 150, 214, 255],
+        TileType::DeepWater => [30, 70, 150, 255],
+        TileType::White => [235, 235, 240, 255],
+    }
+}
+
+/// the tile shown at `pixel` of a map of `size` pixels, the first row of the image is the top, which is north
+pub fn pixel_tile(center: IVec2, scale: i32, size: IVec2, pixel: IVec2) -> IVec2 {
+    center + IVec2::new(pixel.x - size.x / 2, size.y / 2 - pixel.y) * scale
+}
+
+/// where `tile` ends up on a map of `size` pixels, the way back of `pixel_tile`
+pub fn tile_pixel(center: IVec2, scale: i32, size: IVec2, tile: IVec2) -> IVec2 {
+    let offset = (tile - center).as_vec2() / scale as f32;
+    IVec2::new(
+        size.x / 2 + offset.x.floor() as i32,
+        size.y / 2 - offset.y.floor() as i32,
+    )
+}
+
+fn image_size(image: &Image) -> IVec2 {
+    IVec2::new(
+        image.texture_descriptor.size.width as i32,
+        image.texture_descriptor.size.height as i32,
+    )
+}
+
+/// paints the tiles around `center` into `image`, north up and `scale` tiles per pixel
+pub fn paint_map(image: &mut Image, map_tiles: &MapTiles, center: IVec2, scale: i32) {
+    let size = image_size(image);
+
+    for py in 0..size.y {
+        for px in 0..size.x {
+            let tile = pixel_tile(center, scale, size, IVec2::new(px, py));
+            let color = map_tiles.tile(tile).map_or(UNEXPLORED_COLOR, tile_color);
+
+            let at = ((py * size.x + px) * 4) as usize;
+            image.data[at..at + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+/// a square of `2 * radius + 1` pixels around `pixel`, cut off at the edges of the image
+pub fn paint_marker(image: &mut Image, pixel: IVec2, radius: i32, color: [u8; 4]) {
+    let size = image_size(image);
+
+    for py in (pixel.y - radius).max(0)..=(pixel.y + radius).min(size.y - 1) {
+        for px in (pixel.x - radius).max(0)..=(pixel.x + radius).min(size.x - 1) {
+            let at = ((py * size.x + px) * 4) as usize;
+            image.data[at..at + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+pub fn new_map_image(width: u32, height: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &UNEXPLORED_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+#[derive(Component)]
+struct Minimap {
+    image: Handle<Image>,
+    zoom_level: usize,
+}
+
+fn setup_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(new_map_image(MINIMAP_PIXELS, MINIMAP_PIXELS));
+
+    commands
+        .spawn((
+            ImageBundle {
+                image: UiImage::new(image.clone()),
+                // below the full-screen map
+                z_index: ZIndex::Global(i32::MAX - 2),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Percent(1.),
+                    bottom: Val::Percent(1.),
+                    width: Val::Px(MINIMAP_PIXELS as f32),
+                    height: Val::Px(MINIMAP_PIXELS as f32),
+                    border: UiRect::all(Val::Px(2.)),
+                    ..default()
+                },
+                ..default()
+            },
+            BorderColor(Color::BLACK),
+            Interaction::default(),
+            Minimap {
+                image,
+                zoom_level: 0,
+            },
+            Name::new("Minimap"),
+        ))
+        .with_children(|minimap| {
+            // the player always stands in the middle
+            minimap.spawn(NodeBundle {
+                background_color: BackgroundColor(Color::RED),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px((MINIMAP_PIXELS as f32 - MINIMAP_MARKER) / 2. - 2.),
+                    top: Val::Px((MINIMAP_PIXELS as f32 - MINIMAP_MARKER) / 2. - 2.),
+                    width: Val::Px(MINIMAP_MARKER),
+                    height: Val::Px(MINIMAP_MARKER),
+                    ..default()
+                },
+                ..default()
+            });
+        });
+}
+
+fn forget_map(mut explored: ResMut<ExploredChunks>, mut map_tiles: ResMut<MapTiles>) {
+    explored.0.clear();
+    map_tiles.chunks.clear();
+}
+
+/// chunks explored in an earlier session are generated again, their tiles never change
+fn fill_explored_tiles(
+    rng_jesus: Res<RngJesus>,
+    explored: Res<ExploredChunks>,
+    mut map_tiles: ResMut<MapTiles>,
+) {
+    if !explored.is_changed() {
+        return;
+    }
+
+    for chunk in explored.0.iter() {
+        if !map_tiles.chunks.contains_key(chunk) {
+            map_tiles.insert(*chunk, chunk_tiles(&rng_jesus, *chunk));
+        }
+    }
+}
+
+/// copies the tiles of freshly spawned chunks into `MapTiles`
+fn learn_spawned_tiles(
+    chunk_manager: Res<ChunkManager>,
+    mut explored: ResMut<ExploredChunks>,
+    mut map_tiles: ResMut<MapTiles>,
+    tiles: Query<(&GroundTile, &TilePos, &TilemapId), Added<GroundTile>>,
+) {
+    if tiles.is_empty() {
+        return;
+    }
+
+    let chunk_of: HashMap<Entity, IVec2> = chunk_manager
+        .spawned_chunks
+        .iter()
+        .map(|(position, entity)| (*entity, *position))
+        .collect();
+
+    for (ground, tile_pos, tilemap) in tiles.iter() {
+        let Some(chunk) = chunk_of.get(&tilemap.0) else {
+            continue;
+        };
+        let chunk_tiles = map_tiles.chunks.entry(*chunk).or_insert_with(|| {
+            vec![TileType::White; (CHUNK_SIZE.x * CHUNK_SIZE.y) as usize]
+        });
+        chunk_tiles[(tile_pos.x * CHUNK_SIZE.y + tile_pos.y) as usize] = ground.0;
+        explored.0.insert(*chunk);
+    }
+}
+
+fn minimap_zoom(mut minimap: Query<(&Interaction, &mut Minimap), Changed<Interaction>>) {
+    for (interaction, mut minimap) in &mut minimap {
+        if *interaction == Interaction::Pressed {
+            minimap.zoom_level = (minimap.zoom_level + 1) % MINIMAP_ZOOM_LEVELS.len();
+        }
+    }
+}
+
+/// repaints the minimap whenever the player walks onto another tile or the map learned more
+fn draw_minimap(
+    map_tiles: Res<MapTiles>,
+    waypoints: Res<Waypoints>,
+    mut images: ResMut<Assets<Image>>,
+    mut drawn: Local<Option<(IVec2, usize)>>,
+    player: Query<&Transform, With<Player>>,
+    minimap: Query<&Minimap>,
+) {
+    let center = tile_at_world_pos(player.single().translation.truncate());
+    let minimap = minimap.single();
+    if *drawn == Some((center, minimap.zoom_level))
+        && !map_tiles.is_changed()
+        && !waypoints.is_changed()
+    {
+        return;
+    }
+    *drawn = Some((center, minimap.zoom_level));
+
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+    let scale = MINIMAP_ZOOM_LEVELS[minimap.zoom_level];
+    paint_map(image, &map_tiles, center, scale);
+
+    let size = image_size(image);
+    for waypoint in waypoints.0.iter() {
+        paint_marker(image, tile_pixel(center, scale, size, *waypoint), 1, WAYPOINT_COLOR);
+    }
+}
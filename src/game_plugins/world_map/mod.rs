@@ -1,3 +1,5 @@
 pub mod utils;
 pub mod chunk_gen;
+pub mod map_screen;
+pub mod minimap;
 pub mod world_gen;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use bevy::math::IVec2;
+use bevy::math::{IVec2, Vec2};
 use bevy_ecs_tilemap::tiles::TilePos;
 
 use crate::consts::{TILE_SIZE, CHUNK_SIZE};
@@ -48,3 +48,26 @@ pub fn world_to_tiles((x, y): (f32, f32)) -> (i32, i32) {
 
     return (x_tile_count, y_tile_count);
 }
+
+pub fn tiles_to_world(tile_position: IVec2) -> (f32, f32) {
+    (
+        tile_position.x as f32 * TILE_SIZE.x,
+        tile_position.y as f32 * TILE_SIZE.y,
+    )
+}
+
+pub fn tiles_to_chunks(tile_position: IVec2) -> IVec2 {
+    IVec2::new(
+        tile_position.x.div_euclid(CHUNK_SIZE.x as i32),
+        tile_position.y.div_euclid(CHUNK_SIZE.y as i32),
+    )
+}
+
+/// the tile a point in the world is standing on. Tiles are centered on their
+/// position, so this rounds instead of flooring like `world_to_tiles`.
+pub fn tile_at_world_pos(position: Vec2) -> IVec2 {
+    IVec2::new(
+        (position.x / TILE_SIZE.x).round() as i32,
+        (position.y / TILE_SIZE.y).round() as i32,
+    )
+}
@@ -7,7 +7,15 @@ use bevy_ecs_tilemap::{map::TilemapRenderSettings, TilemapPlugin, tiles::TilePos
 use rand::{thread_rng, Rng};
 
 
-use crate::{consts::{RENDER_CHUNK_SIZE,CHUNK_SIZE}, game_plugins::player::Player, zoom::WorldView};
+use crate::{
+    consts::{RENDER_CHUNK_SIZE,CHUNK_SIZE},
+    game_plugins::{
+        console::{typed, Console, ConsoleApp, ConsoleCommand},
+        player::Player,
+    },
+    game_state::GameState,
+    zoom::WorldView,
+};
 
 use super::{chunk_gen::{spawn_chunks, TileType}, utils::world_to_chunks};
 
@@ -40,6 +48,22 @@ impl RngJesus {
 
         Self { seed, seed2, biom_seed }
     }
+
+    /// the seed for whatever `salt` stands for in one chunk. Every chunk gets its own,
+    /// negative coordinates included, and trees and pigs of a chunk don't share one.
+    pub fn chunk_seed(&self, chunk_position: IVec2, salt: u64) -> u64 {
+        let position = ((chunk_position.x as u32 as u64) << 32) | (chunk_position.y as u32 as u64);
+        // both steps are one-to-one, so different chunks can't end up with the same seed
+        mix_seed(position ^ mix_seed(((self.seed2 as u64) << 32) ^ salt))
+    }
+}
+
+/// splitmix64, spreads every bit of `value` over the whole result
+fn mix_seed(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
 
@@ -88,6 +112,32 @@ impl ChunkManager {
 #[derive(Component)]
 pub struct TileMap;
 
+/// Sent after a chunk got spawned around the player, so other plugins can populate it.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkSpawned {
+    pub position: IVec2,
+    pub entity: Entity,
+}
+
+/// Sent right before a chunk gets despawned, so other plugins can store what lives on it.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkDespawned {
+    pub position: IVec2,
+}
+
+/// The order in which chunks come and go within one frame.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChunkSet {
+    /// chunks get spawned and despawned around the player
+    Stream,
+    /// what was stored for the new chunks gets fetched from disk
+    Fetch,
+    /// plugins fill the new chunks and store what lives on the despawned ones
+    Content,
+    /// the stored content of despawned chunks goes to disk
+    Persist,
+}
+
 pub struct WorldGenPlugin;
 
 impl Plugin for WorldGenPlugin {
@@ -95,28 +145,109 @@ impl Plugin for WorldGenPlugin {
         app
             .init_resource::<ChunkManager>()
             .init_resource::<RngJesus>()
+            .add_event::<ChunkSpawned>()
+            .add_event::<ChunkDespawned>()
             // `TilemapRenderSettings` must be added before the `TilemapPlugin`.
             .insert_resource(TilemapRenderSettings {
                 render_chunk_size: RENDER_CHUNK_SIZE,
                 ..Default::default()
             })
             .add_plugins(TilemapPlugin)
+            .configure_sets(
+                Update,
+                (ChunkSet::Stream, ChunkSet::Fetch, ChunkSet::Content, ChunkSet::Persist).chain(),
+            )
             .add_systems(
                 Update,
                 (
                     spawn_chunks_around_camera,
                     despawn_chunks_out_of_range_of_camera,
-                ),
+                )
+                    .in_set(ChunkSet::Stream)
+                    .run_if(in_state(GameState::GAME)),
+            )
+            .add_systems(OnEnter(GameState::MENU), (despawn_all_chunks, start_regenerated_world))
+            .add_console_command("seed", "seed - shows the seeds of this world", seed_command)
+            .add_console_command("regen", "regen - starts a new world with new seeds", regen_command)
+            .add_console_command(
+                "chunks reload",
+                "chunks reload - despawns every chunk, they come back from the store",
+                reload_chunks_command
+                    .after(spawn_chunks_around_camera)
+                    .in_set(ChunkSet::Stream),
             );
     }
 }
 
+/// Set by the `regen` command, the world goes through the menu and comes back with new seeds.
+#[derive(Resource)]
+struct RegenerateWorld;
+
+fn seed_command(
+    mut commands: EventReader<ConsoleCommand>,
+    rng_jesus: Res<RngJesus>,
+    mut console: ResMut<Console>,
+) {
+    for _ in typed(&mut commands, "seed") {
+        console.print(format!(
+            "seed: {}, seed2: {}, biom seed: {}",
+            rng_jesus.seed, rng_jesus.seed2, rng_jesus.biom_seed
+        ));
+    }
+}
+
+fn regen_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut cmds: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for _ in typed(&mut commands, "regen") {
+        cmds.insert_resource(RegenerateWorld);
+        next_state.set(GameState::MENU);
+    }
+}
+
+/// going back to the menu tore the old world down, now the new one starts like a new game
+fn start_regenerated_world(
+    mut commands: Commands,
+    regenerate: Option<Res<RegenerateWorld>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if regenerate.is_none() {
+        return;
+    }
+
+    commands.remove_resource::<RegenerateWorld>();
+    commands.insert_resource(RngJesus::default());
+    next_state.set(GameState::GAME);
+}
+
+/// the chunks go away like out of range ones, so what lives on them is stored first
+fn reload_chunks_command(
+    mut commands: EventReader<ConsoleCommand>,
+    mut cmds: Commands,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut chunk_despawned: EventWriter<ChunkDespawned>,
+    mut console: ResMut<Console>,
+) {
+    for _ in typed(&mut commands, "chunks reload") {
+        let count = chunk_manager.spawned_chunks.len();
+        for (position, entity) in chunk_manager.spawned_chunks.drain() {
+            chunk_despawned.send(ChunkDespawned { position });
+            cmds.entity(entity).despawn_recursive();
+        }
+        chunk_manager.spawned_tiles.clear();
+        console.print(format!("reloading {} chunks", count));
+    }
+}
+
 pub fn spawn_chunks_around_camera(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut chunk_manager: ResMut<ChunkManager>,
     seed: Res<RngJesus>,
     player_pos: Query<&Transform, With<Player>>,
+    mut chunk_spawned: EventWriter<ChunkSpawned>,
 ) {
 
     let player_pos = player_pos.single();
@@ -132,6 +263,7 @@ pub fn spawn_chunks_around_camera(
                 let start = Instant::now();
                 let entity = spawn_chunks(&mut commands, &asset_server, &seed, chunk);
                 chunk_manager.add_new_chunk(chunk, entity);
+                chunk_spawned.send(ChunkSpawned { position: chunk, entity });
                 let duration = start.elapsed();
 
                 debug!("duration of chunk gen {} {} was {} seconds", x, y, duration.as_secs_f32());
@@ -140,10 +272,19 @@ pub fn spawn_chunks_around_camera(
     }
 }
 
+/// tears the whole world down when going back to the main menu
+fn despawn_all_chunks(mut commands: Commands, mut chunk_manager: ResMut<ChunkManager>) {
+    for (_, entity) in chunk_manager.spawned_chunks.drain() {
+        commands.entity(entity).despawn_recursive();
+    }
+    chunk_manager.spawned_tiles.clear();
+}
+
 fn despawn_chunks_out_of_range_of_camera(
     mut commands: Commands,
     mut chunk_manager: ResMut<ChunkManager>,
     player_pos: Query<&mut Transform, With<Player>>,
+    mut chunk_despawned: EventWriter<ChunkDespawned>,
 ) {
     let player_pos = player_pos.single();
     let (chunk_x, chunk_y) = world_to_chunks((player_pos.translation.x, player_pos.translation.y));
@@ -161,7 +302,10 @@ fn despawn_chunks_out_of_range_of_camera(
         if !allowed_ivec2s.contains(&val) {
             let entity = chunk_manager.remove_chunk(&val);
             match entity {
-                Some(val) => commands.entity(val).despawn_recursive(),
+                Some(entity) => {
+                    chunk_despawned.send(ChunkDespawned { position: val });
+                    commands.entity(entity).despawn_recursive()
+                }
                 None => error!("Tried to delete chunk {:?}- failed", val),
             }
         }
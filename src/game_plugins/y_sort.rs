@@ -0,0 +1,84 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use super::player::Player;
+
+// sorted sprites get a z between these, the chunks are below and the weather above
+const Y_SORT_MIN_Z: f32 = 1.;
+const Y_SORT_MAX_Z: f32 = 9.;
+// world units above and below the sort origin that still sort, far more than fits on screen
+const Y_SORT_SPAN: f32 = 100_000.;
+
+/// Draws sprites lower on the screen over the ones above them, so walking behind a tree
+/// hides the player and walking in front of it doesn't. Anything with a `YSort` takes part.
+pub struct YSortPlugin;
+
+impl Plugin for YSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<YSortOrigin>().add_systems(
+            PostUpdate,
+            (move_sort_origin, y_sort)
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Sorts the sprite by where its feet are, the bottom edge of the sprite.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct YSort;
+
+/// The y the z values are worked out around. It follows the player in big jumps, so
+/// sprites keep their z unless they move, however far the player travels.
+#[derive(Resource, Debug, Default)]
+pub struct YSortOrigin(pub f32);
+
+/// the z of a sprite with its feet at `foot_y`, lower feet are drawn on top
+pub fn y_sort_z(foot_y: f32, origin: f32) -> f32 {
+    let offset = ((foot_y - origin) / Y_SORT_SPAN).clamp(-1., 1.);
+    let middle = (Y_SORT_MIN_Z + Y_SORT_MAX_Z) / 2.;
+    middle - offset * (Y_SORT_MAX_Z - Y_SORT_MIN_Z) / 2.
+}
+
+fn move_sort_origin(mut origin: ResMut<YSortOrigin>, player: Query<&Transform, With<Player>>) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    // a quarter of the span away there is still plenty of room for everything on screen
+    let step = Y_SORT_SPAN / 4.;
+    if (player.translation.y - origin.0).abs() > step {
+        origin.0 = (player.translation.y / step).round() * step;
+    }
+}
+
+fn y_sort(
+    origin: Res<YSortOrigin>,
+    images: Res<Assets<Image>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut sprites: Query<
+        (
+            &mut Transform,
+            Option<&Handle<Image>>,
+            Option<(&Handle<TextureAtlas>, &TextureAtlasSprite)>,
+        ),
+        With<YSort>,
+    >,
+) {
+    for (mut transform, image, atlas) in &mut sprites {
+        // sprites are drawn around their center, until the image is loaded the center has to do
+        let height = match (image, atlas) {
+            (_, Some((atlas, sprite))) => atlases
+                .get(atlas)
+                .and_then(|atlas| atlas.textures.get(sprite.index))
+                .map_or(0., |rect| rect.height()),
+            (Some(image), None) => images.get(image).map_or(0., |image| image.size_f32().y),
+            (None, None) => 0.,
+        };
+
+        let foot_y = transform.translation.y - height / 2. * transform.scale.y;
+        let z = y_sort_z(foot_y, origin.0);
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
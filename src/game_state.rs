@@ -1,41 +1,59 @@
-#[derive(Resource, States, Debug, Hash, PartialEq, Eq, Clone, Reflect)]
+use bevy::prelude::*;
+
+use crate::game_plugins::actions::{Action, ActionState};
+
+#[derive(States, Debug, Hash, PartialEq, Eq, Clone, Copy, Reflect)]
 pub enum GameState {
     MENU,
     GAME,
+    PAUSED,
 }
 
 impl Default for GameState {
     fn default() -> Self {
-        Self::GAME
+        Self::MENU
     }
 }
 
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .add_systems(
+                Update,
+                toggle_pause.run_if(in_state(GameState::GAME).or_else(in_state(GameState::PAUSED))),
+            )
+            .add_systems(OnEnter(GameState::PAUSED), pause_time)
+            .add_systems(OnExit(GameState::PAUSED), unpause_time);
+    }
+}
 
-fn change_game_state(
-    game_state: ResMut<State<GameState>>,
+fn toggle_pause(
+    game_state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
-    input: Res<Input<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
-    if !input.just_pressed(KeyCode::M) {
+    if !actions.just_pressed(Action::Pause) {
         return;
     }
 
-    // let mut game_state: &GameState = game_state.get();
-
     let new = match game_state.get() {
-        GameState::GAME => GameState::MENU,
-        GameState::MENU => GameState::GAME,
+        GameState::GAME => GameState::PAUSED,
+        GameState::PAUSED => GameState::GAME,
+        GameState::MENU => return,
     };
     next_state.set(new);
-    // if game_state.get() == &new {return};
 
-    // let boxed = Box::new(new);
-    // let boxed2 = Box::new(boxed.as_reflect());
+    info!("game state {:?}", new);
+}
 
-    // match game_state.set(boxed2) {
-    //     Ok(_) => info!("successfully set new game_state"),
-    //     Err(e) => error!("cant set new game state {:?}", e)
-    // }
+// everything ticking with `Time` stands still while paused, even in systems
+// that are not gated by the game state
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
 
-    info!("game state {:?}", game_state);
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
 }
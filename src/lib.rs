@@ -1,7 +1,7 @@
 // pub mod player;
-// pub mod pig;
 pub mod game_plugins;
 pub mod seeded_rng;
 pub mod zoom;
 pub mod consts;
+pub mod game_state;
 // pub mod grid;
\ No newline at end of file
@@ -1,21 +1,36 @@
-use bevy::{
-    input::common_conditions::input_toggle_active,
-    prelude::*,
-    window::close_on_esc,
-};
+use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
+mod debug_hud;
 mod game_plugins;
-// mod pig;
+mod game_state;
+mod menu;
 mod ui;
 mod zoom;
 // local uses
 use game_plugins::{
+    actions::{action_toggle_active, Action, ActionsPlugin},
+    animation::AnimationPlugin,
+    combat::CombatPlugin,
+    console::ConsolePlugin,
+    daytime::DaytimePlugin,
+    economy::EconomyPlugin,
+    enemy::EnemyPlugin,
+    movement::MovementPlugin,
+    pig::PigPlugin,
     player::{Player, PlayerPlugin},
+    save::SavePlugin,
+    seasons::SeasonPlugin,
+    sound::SoundPlugin,
     tree::TreePlugin,
-    world_map::world_gen::WorldGenPlugin,
+    weather::WeatherPlugin,
+    world_map::{map_screen::MapScreenPlugin, minimap::MinimapPlugin, world_gen::WorldGenPlugin},
+    y_sort::YSortPlugin,
 };
 
+use debug_hud::DebugHudPlugin;
+use game_state::GameStatePlugin;
+use menu::MenuPlugin;
 use ui::GameUI;
 use zoom::ScaleableWorldViewPlugin;
 
@@ -39,26 +54,36 @@ fn main() {
                 .build(),
         )
         .add_plugins(
-            WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::F2)),
+            WorldInspectorPlugin::default().run_if(action_toggle_active(false, Action::ToggleInspector)),
         )
+        .add_plugins((
+            ActionsPlugin,
+            GameStatePlugin,
+            MenuPlugin,
+            DebugHudPlugin,
+            ConsolePlugin,
+            MovementPlugin,
+            AnimationPlugin,
+            YSortPlugin,
+            SoundPlugin,
+        ))
         .add_plugins((
             ScaleableWorldViewPlugin,
             TreePlugin,
+            PigPlugin,
             PlayerPlugin,
             GameUI,
             WorldGenPlugin,
+            EconomyPlugin,
+            CombatPlugin,
+            EnemyPlugin,
+            SavePlugin,
+            DaytimePlugin,
+            WeatherPlugin,
+            SeasonPlugin,
+            MinimapPlugin,
+            MapScreenPlugin,
         ))
-        .add_systems(
-            Update,
-            (
-                close_on_esc,
-                // spawn_chunks_around_camera2,
-                // spawn_chunks_around_camera
-                // change_world_scale,
-                // character_movement
-                //     despawn_outofrange_chunks,
-            ),
-        )
         // .add_systems(Startup, tile_map_6init)
         .run();
 }
@@ -0,0 +1,472 @@
+use bevy::{app::AppExit, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::game_plugins::{
+    actions::{Action, Binding, Bindings},
+    daytime::ClockSettings,
+    save::{chunk_store::ChunkBackend, LoadGame, SaveGame, SaveSlots},
+    sound::{AudioSettings, VolumeChannel},
+    tree::TreeSettings,
+    world_map::world_gen::RngJesus,
+};
+use crate::game_state::GameState;
+use crate::zoom::CameraFollow;
+
+const MENU_FONT: &str = "fonts/Dragon_Fire_font.otf";
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const BUTTON_HOVER_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+const BUTTON_DISABLED_COLOR: Color = Color::rgb(0.08, 0.08, 0.08);
+const CONFLICT_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuPage>()
+            .init_resource::<Rebinding>()
+            .add_systems(OnEnter(GameState::MENU), open_main_page)
+            .add_systems(OnExit(GameState::MENU), despawn_menu)
+            .add_systems(OnEnter(GameState::PAUSED), spawn_pause_menu)
+            .add_systems(OnExit(GameState::PAUSED), despawn_menu)
+            .add_systems(
+                Update,
+                (capture_binding, show_menu_page).run_if(in_state(GameState::MENU)),
+            )
+            .add_systems(Update, menu_buttons);
+    }
+}
+
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuPage {
+    #[default]
+    Main,
+    Load,
+    Settings,
+    Controls,
+    Audio,
+}
+
+/// The action waiting for its new key on the controls page.
+#[derive(Resource, Default, Debug)]
+struct Rebinding(Option<Action>);
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuButton {
+    NewWorld,
+    LoadWorld,
+    // index into `SaveSlots`
+    LoadSlot(usize),
+    Settings,
+    Quit,
+    Back,
+    ToggleSeedSpread,
+    ToggleChunkBackend,
+    DayLength,
+    ToggleCameraClamp,
+    Controls,
+    Rebind(Action),
+    ResetBindings,
+    Audio,
+    Volume(VolumeChannel),
+    Resume,
+    SaveWorld,
+    MainMenu,
+}
+
+#[derive(Component)]
+struct Disabled;
+
+fn open_main_page(mut page: ResMut<MenuPage>) {
+    // always rebuild, even if the page did not change since the last visit
+    *page = MenuPage::Main;
+    page.set_changed();
+}
+
+fn despawn_menu(mut commands: Commands, roots: Query<Entity, With<MenuRoot>>) {
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+fn menu_root(background: Color) -> NodeBundle {
+    NodeBundle {
+        background_color: BackgroundColor(background),
+        z_index: ZIndex::Global(i32::MAX),
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(12.),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn spawn_title(parent: &mut ChildBuilder, font: &Handle<Font>, value: &str) {
+    parent.spawn(
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size: 64.,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::bottom(Val::Px(24.)),
+            ..default()
+        }),
+    );
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: MenuButton,
+    enabled: bool,
+) {
+    let mut entity = parent.spawn((
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(260.),
+                padding: UiRect::all(Val::Px(8.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(if enabled {
+                BUTTON_COLOR
+            } else {
+                BUTTON_DISABLED_COLOR
+            }),
+            ..default()
+        },
+        button,
+    ));
+    if !enabled {
+        entity.insert(Disabled);
+    }
+
+    entity.with_children(|button| {
+        button.spawn(TextBundle::from_section(
+            label,
+            TextStyle {
+                font: font.clone(),
+                font_size: 28.,
+                color: if enabled { Color::WHITE } else { Color::GRAY },
+            },
+        ));
+    });
+}
+
+/// a smaller button for the controls page, red while its keys are bound to something else too
+fn spawn_binding_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    action: Action,
+    conflict: bool,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(400.),
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                background_color: BackgroundColor(BUTTON_COLOR),
+                ..default()
+            },
+            MenuButton::Rebind(action),
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 20.,
+                    color: if conflict { CONFLICT_COLOR } else { Color::WHITE },
+                },
+            ));
+        });
+}
+
+/// the next key or gamepad button pressed becomes the binding of the action waiting for one
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    let binding = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Button(button.button_type))
+        });
+    let Some(binding) = binding else {
+        return;
+    };
+
+    bindings.rebind(action, binding);
+    rebinding.0 = None;
+    if let Err(e) = bindings.save() {
+        error!("{}", e);
+    }
+}
+
+fn show_menu_page(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    page: Res<MenuPage>,
+    tree_settings: Res<TreeSettings>,
+    save_slots: Res<SaveSlots>,
+    chunk_backend: Res<ChunkBackend>,
+    clock_settings: Res<ClockSettings>,
+    camera_follow: Res<CameraFollow>,
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+    audio_settings: Res<AudioSettings>,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    if !page.is_changed()
+        && !tree_settings.is_changed()
+        && !save_slots.is_changed()
+        && !chunk_backend.is_changed()
+        && !clock_settings.is_changed()
+        && !camera_follow.is_changed()
+        && !bindings.is_changed()
+        && !rebinding.is_changed()
+        && !audio_settings.is_changed()
+    {
+        return;
+    }
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let font: Handle<Font> = asset_server.load(MENU_FONT);
+    commands
+        .spawn((MenuRoot, menu_root(Color::rgb(0.05, 0.1, 0.05)), Name::new("Menu")))
+        .with_children(|root| match *page {
+            MenuPage::Main => {
+                spawn_title(root, &font, "AutoRPG");
+                spawn_button(root, &font, "New World", MenuButton::NewWorld, true);
+                spawn_button(
+                    root,
+                    &font,
+                    "Load World",
+                    MenuButton::LoadWorld,
+                    !save_slots.0.is_empty(),
+                );
+                spawn_button(root, &font, "Settings", MenuButton::Settings, true);
+                spawn_button(root, &font, "Quit", MenuButton::Quit, true);
+            }
+            MenuPage::Load => {
+                spawn_title(root, &font, "Load World");
+                for (index, slot) in save_slots.0.iter().enumerate() {
+                    spawn_button(root, &font, slot, MenuButton::LoadSlot(index), true);
+                }
+                spawn_button(root, &font, "Back", MenuButton::Back, true);
+            }
+            MenuPage::Settings => {
+                spawn_title(root, &font, "Settings");
+                let seed_spread = if tree_settings.seed_spread { "On" } else { "Off" };
+                spawn_button(
+                    root,
+                    &font,
+                    &format!("Trees spread seeds: {}", seed_spread),
+                    MenuButton::ToggleSeedSpread,
+                    true,
+                );
+                // only used for new worlds, saved ones keep what they were created with
+                spawn_button(
+                    root,
+                    &font,
+                    &format!("Chunk storage: {}", chunk_backend.name()),
+                    MenuButton::ToggleChunkBackend,
+                    true,
+                );
+                spawn_button(
+                    root,
+                    &font,
+                    &format!("Day length: {} min", clock_settings.day_length / 60.),
+                    MenuButton::DayLength,
+                    true,
+                );
+                let camera_clamp = if camera_follow.clamp_to_world { "On" } else { "Off" };
+                spawn_button(
+                    root,
+                    &font,
+                    &format!("Camera stays in the world: {}", camera_clamp),
+                    MenuButton::ToggleCameraClamp,
+                    true,
+                );
+                spawn_button(root, &font, "Controls", MenuButton::Controls, true);
+                spawn_button(root, &font, "Audio", MenuButton::Audio, true);
+                spawn_button(root, &font, "Back", MenuButton::Back, true);
+            }
+            MenuPage::Controls => {
+                spawn_title(root, &font, "Controls");
+                root.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(820.),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(6.),
+                        column_gap: Val::Px(12.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|list| {
+                    for action in Action::iter() {
+                        let label = if rebinding.0 == Some(action) {
+                            format!("{}: press a key...", action.name())
+                        } else {
+                            let keys: Vec<String> =
+                                bindings.get(action).iter().map(Binding::name).collect();
+                            format!("{}: {}", action.name(), keys.join(" / "))
+                        };
+                        let conflict = bindings.in_conflict(action);
+                        spawn_binding_button(list, &font, &label, action, conflict);
+                    }
+                });
+                spawn_button(root, &font, "Reset to defaults", MenuButton::ResetBindings, true);
+                spawn_button(root, &font, "Back", MenuButton::Back, true);
+            }
+            MenuPage::Audio => {
+                spawn_title(root, &font, "Audio");
+                for channel in VolumeChannel::ALL {
+                    spawn_button(
+                        root,
+                        &font,
+                        &format!(
+                            "{} volume: {}%",
+                            channel.name(),
+                            (audio_settings.get(channel) * 100.).round()
+                        ),
+                        MenuButton::Volume(channel),
+                        true,
+                    );
+                }
+                spawn_button(root, &font, "Back", MenuButton::Back, true);
+            }
+        });
+}
+
+fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font: Handle<Font> = asset_server.load(MENU_FONT);
+
+    commands
+        .spawn((MenuRoot, menu_root(Color::BLACK.with_a(0.6)), Name::new("Pause Menu")))
+        .with_children(|root| {
+            spawn_title(root, &font, "Paused");
+            spawn_button(root, &font, "Resume", MenuButton::Resume, true);
+            spawn_button(root, &font, "Save", MenuButton::SaveWorld, true);
+            spawn_button(root, &font, "Main Menu", MenuButton::MainMenu, true);
+        });
+}
+
+fn menu_buttons(
+    mut commands: Commands,
+    mut page: ResMut<MenuPage>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut tree_settings: ResMut<TreeSettings>,
+    mut chunk_backend: ResMut<ChunkBackend>,
+    mut clock_settings: ResMut<ClockSettings>,
+    mut camera_follow: ResMut<CameraFollow>,
+    mut bindings: ResMut<Bindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut audio_settings: ResMut<AudioSettings>,
+    save_slots: Res<SaveSlots>,
+    mut save: EventWriter<SaveGame>,
+    mut load: EventWriter<LoadGame>,
+    mut exit: EventWriter<AppExit>,
+    mut buttons: Query<
+        (&Interaction, &MenuButton, &mut BackgroundColor),
+        (Changed<Interaction>, Without<Disabled>),
+    >,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Hovered => *color = BackgroundColor(BUTTON_HOVER_COLOR),
+            Interaction::None => *color = BackgroundColor(BUTTON_COLOR),
+            Interaction::Pressed => match button {
+                MenuButton::NewWorld => {
+                    // a new random seed, the world gets generated as soon as the game starts
+                    commands.insert_resource(RngJesus::default());
+                    next_state.set(GameState::GAME);
+                }
+                MenuButton::LoadWorld => *page = MenuPage::Load,
+                MenuButton::LoadSlot(index) => {
+                    if let Some(slot) = save_slots.0.get(*index) {
+                        load.send(LoadGame { slot: slot.clone() });
+                    }
+                }
+                MenuButton::Settings => *page = MenuPage::Settings,
+                MenuButton::Back => {
+                    *page = match *page {
+                        MenuPage::Controls | MenuPage::Audio => MenuPage::Settings,
+                        _ => MenuPage::Main,
+                    };
+                    rebinding.0 = None;
+                }
+                MenuButton::ToggleSeedSpread => {
+                    tree_settings.seed_spread = !tree_settings.seed_spread;
+                }
+                MenuButton::ToggleChunkBackend => *chunk_backend = chunk_backend.next(),
+                MenuButton::DayLength => {
+                    clock_settings.day_length = clock_settings.next_day_length();
+                }
+                MenuButton::ToggleCameraClamp => {
+                    camera_follow.clamp_to_world = !camera_follow.clamp_to_world;
+                }
+                MenuButton::Controls => *page = MenuPage::Controls,
+                // clicking the action that waits for a key again gives up on it
+                MenuButton::Rebind(action) => {
+                    rebinding.0 = if rebinding.0 == Some(*action) {
+                        None
+                    } else {
+                        Some(*action)
+                    };
+                }
+                MenuButton::ResetBindings => {
+                    *bindings = Bindings::default();
+                    rebinding.0 = None;
+                    if let Err(e) = bindings.save() {
+                        error!("{}", e);
+                    }
+                }
+                MenuButton::Audio => *page = MenuPage::Audio,
+                MenuButton::Volume(channel) => {
+                    audio_settings.step(*channel);
+                    if let Err(e) = audio_settings.save() {
+                        error!("{}", e);
+                    }
+                }
+                MenuButton::Quit => exit.send(AppExit),
+                MenuButton::Resume => next_state.set(GameState::GAME),
+                MenuButton::SaveWorld => save.send(SaveGame),
+                MenuButton::MainMenu => next_state.set(GameState::MENU),
+            },
+        }
+    }
+}
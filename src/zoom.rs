@@ -1,27 +1,132 @@
-use crate::{game_plugins::player::Player, consts::MAX_ZOOM};
-use bevy::{input::mouse::MouseWheel, prelude::*};
+use crate::{
+    consts::TILE_SIZE,
+    game_plugins::{
+        actions::{Action, ActionState},
+        player::{Player, PlayerTeleported},
+        world_map::{map_screen::map_closed, utils::chunks_to_world, world_gen::ChunkManager},
+    },
+    game_state::GameState,
+};
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
+use bevy_ecs_tilemap::tiles::TilePos;
+
+// how fast the view drifts back onto the player after zooming towards the cursor
+const LOOK_OFFSET_RETURN: f32 = 0.5;
+// zoom steps per second with a gamepad trigger pressed all the way
+const TRIGGER_ZOOM_SPEED: f32 = 2.;
 
 pub struct ScaleableWorldViewPlugin;
 
 impl Plugin for ScaleableWorldViewPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-        .add_systems(Update, change_world_scale);
+        app.init_resource::<CameraFollow>()
+        .init_resource::<ZoomSettings>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                // the wheel zooms the full-screen map while that is open
+                change_world_scale.run_if(map_closed),
+                ease_zoom,
+            )
+                .chain()
+                .run_if(in_state(GameState::GAME)),
+        )
+        // after everything moved the player this frame, but before the transforms are propagated
+        .add_systems(
+            PostUpdate,
+            follow_player
+                .before(TransformSystem::TransformPropagate)
+                .run_if(in_state(GameState::GAME)),
+        )
+        .register_type::<CameraFollow>()
+        .register_type::<ZoomSettings>();
+    }
+}
+
+/// How the camera follows the player around.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct CameraFollow {
+    // how fast the camera catches up with the player, 0 locks it onto the player
+    pub smoothing: f32,
+    // the player can walk this far from the center of the view before the camera moves
+    pub dead_zone: Vec2,
+    // a player that moved further than this in one frame got teleported even without
+    // a `PlayerTeleported`, the camera jumps along
+    pub snap_distance: f32,
+    // keeps the view inside the chunks that are loaded, so no void shows at the edges
+    pub clamp_to_world: bool,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            smoothing: 8.,
+            dead_zone: Vec2::new(24., 16.),
+            snap_distance: 512.,
+            clamp_to_world: false,
+        }
+    }
+}
+
+/// How far the camera can zoom and how it gets there.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct ZoomSettings {
+    // 1 shows 256x144 world pixels, every zoom level above shows that much more
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    // factor one scroll notch or key press zooms by
+    pub step: f32,
+    // how fast the zoom eases towards where it was asked to go
+    pub speed: f32,
+}
+
+impl Default for ZoomSettings {
+    fn default() -> Self {
+        Self {
+            min_zoom: 1.,
+            max_zoom: 16.,
+            step: 2.,
+            speed: 10.,
+        }
     }
 }
 
 #[derive(Component)]
 pub struct WorldView {
     zoom_factor: f32,
+    // where `zoom_factor` eases towards
+    target_zoom: f32,
+    // world position that stays under the cursor while zooming, `None` zooms on the center
+    zoom_anchor: Option<Vec2>,
+    // how far the view was pulled off the player by zooming towards the cursor,
+    // the camera follows the player plus this and it slowly returns to zero
+    pub look_offset: Vec2,
 }
 
 impl Default for WorldView {
     fn default() -> Self {
-        Self { zoom_factor: 1.0 }
+        Self {
+            zoom_factor: 1.0,
+            target_zoom: 1.0,
+            zoom_anchor: None,
+            look_offset: Vec2::ZERO,
+        }
     }
 }
 
 impl WorldView {
+    pub fn zoom_factor(&self) -> f32 {
+        self.zoom_factor
+    }
+
     pub fn get_zoom_state(&self) -> bevy::render::camera::ScalingMode {
         bevy::render::camera::ScalingMode::AutoMin {
             min_width: 256.0 * self.zoom_factor,
@@ -29,21 +134,101 @@ impl WorldView {
         }
     }
 
-    pub fn zoom_in(&mut self) {
-        self.zoom_factor *= 0.5;
-        if self.zoom_factor <= 1. {
-            self.zoom_factor = 1.;
-            return;
-        }
+    /// zooms by `steps` levels, positive ones zoom in
+    pub fn zoom_by(&mut self, steps: f32, anchor: Option<Vec2>, settings: &ZoomSettings) {
+        self.target_zoom = (self.target_zoom * settings.step.powf(-steps))
+            .clamp(settings.min_zoom, settings.max_zoom);
+        self.zoom_anchor = anchor;
     }
+}
 
-    pub fn zoom_out(&mut self) {
-        self.zoom_factor *= 2.0;
-        if self.zoom_factor >= MAX_ZOOM {
-            self.zoom_factor = MAX_ZOOM;
-            return;
+/// where the camera has to be so the player is inside the dead zone again
+fn follow_target(camera: Vec2, player: Vec2, dead_zone: Vec2) -> Vec2 {
+    let offset = player - camera;
+    camera + offset - offset.clamp(-dead_zone, dead_zone)
+}
+
+/// the smallest and largest point covered by the loaded chunks
+fn loaded_world_bounds(chunk_manager: &ChunkManager) -> Option<Rect> {
+    let min = chunk_manager.spawned_chunks.keys().copied().reduce(IVec2::min)?;
+    let max = chunk_manager.spawned_chunks.keys().copied().reduce(IVec2::max)?;
+    let corner = |chunk: IVec2| {
+        let (x, y) = chunks_to_world(chunk, TilePos { x: 0, y: 0 });
+        // tiles are centered on their position
+        Vec2::new(x - TILE_SIZE.x / 2., y - TILE_SIZE.y / 2.)
+    };
+
+    Some(Rect::from_corners(corner(min), corner(max + IVec2::ONE)))
+}
+
+/// keeps a view of `half_size` inside `bounds`, a view wider than the bounds stays centered on them
+fn clamp_view(center: Vec2, half_size: Vec2, bounds: Rect) -> Vec2 {
+    let clamp_axis = |center: f32, half: f32, min: f32, max: f32| {
+        if max - min <= half * 2. {
+            (min + max) / 2.
+        } else {
+            center.clamp(min + half, max - half)
+        }
+    };
+
+    Vec2::new(
+        clamp_axis(center.x, half_size.x, bounds.min.x, bounds.max.x),
+        clamp_axis(center.y, half_size.y, bounds.min.y, bounds.max.y),
+    )
+}
+
+pub fn follow_player(
+    time: Res<Time>,
+    settings: Res<CameraFollow>,
+    chunk_manager: Res<ChunkManager>,
+    mut last_player: Local<Option<Vec2>>,
+    mut teleports: EventReader<PlayerTeleported>,
+    player: Query<&Transform, (With<Player>, Without<WorldView>)>,
+    mut camera: Query<
+        (&mut Transform, &OrthographicProjection, &mut WorldView),
+        Without<Player>,
+    >,
+) {
+    let (mut transform, projection, mut world_view) = camera.single_mut();
+    let current = transform.translation.truncate();
+    let half_size = projection.area.half_size();
+    // however far behind the camera is, the player never leaves the view
+    let leash = half_size * 0.8;
+
+    world_view.look_offset = world_view.look_offset.clamp(-leash, leash)
+        * (-LOOK_OFFSET_RETURN * time.delta_seconds()).exp();
+    let player = player.single().translation.truncate();
+    let followed = player + world_view.look_offset;
+
+    // the distance catches teleports nobody told us about
+    let teleported = teleports.read().count() > 0
+        || last_player.is_none_or(|last| last.distance(player) > settings.snap_distance);
+    *last_player = Some(player);
+
+    let mut next = if teleported {
+        world_view.look_offset = Vec2::ZERO;
+        player
+    } else {
+        let target = follow_target(current, followed, settings.dead_zone);
+        if settings.smoothing <= 0. {
+            target
+        } else {
+            // frame rate independent easing towards the target
+            let t = 1. - (-settings.smoothing * time.delta_seconds()).exp();
+            current.lerp(target, t)
+        }
+    };
+
+    next = next.clamp(player - leash, player + leash);
+
+    if settings.clamp_to_world {
+        if let Some(bounds) = loaded_world_bounds(&chunk_manager) {
+            next = clamp_view(next, half_size, bounds);
         }
     }
+
+    transform.translation.x = next.x;
+    transform.translation.y = next.y;
 }
 
 fn setup(mut commands: Commands) {
@@ -55,41 +240,71 @@ fn setup(mut commands: Commands) {
     commands.spawn((camera, world_view));
 }
 
+/// the mouse wheel, the zoom actions and the gamepad triggers zoom, the wheel towards
+/// whatever is under the cursor
 pub fn change_world_scale(
-    mut world_view: Query<
-        (&mut OrthographicProjection, &mut WorldView),
-        (Without<Player>, With<WorldView>),
-    >,
-    input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    settings: Res<ZoomSettings>,
+    actions: Res<ActionState>,
     mut scroll_evr: EventReader<MouseWheel>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut world_view: Query<(&Camera, &GlobalTransform, &mut WorldView)>,
 ) {
+    let (camera, camera_transform, mut world_view) = world_view.single_mut();
 
-    if input.pressed(KeyCode::ControlLeft) || input.pressed(KeyCode::ControlLeft) {
-        // info!("in control loop");
-        let (mut projection, mut world_view) = world_view.single_mut();
+    if actions.just_pressed(Action::ZoomIn) {
+        world_view.zoom_by(1., None, &settings);
+    } else if actions.just_pressed(Action::ZoomOut) {
+        world_view.zoom_by(-1., None, &settings);
+    } else if actions.zoom() != 0. {
+        world_view.zoom_by(actions.zoom() * TRIGGER_ZOOM_SPEED * time.delta_seconds(), None, &settings);
+    }
 
-        if input.just_pressed(KeyCode::Minus) {
-            world_view.zoom_out();
-            projection.scaling_mode = world_view.get_zoom_state();
-        } else if input.just_pressed(KeyCode::Plus) {
-            world_view.zoom_in();
-            projection.scaling_mode = world_view.get_zoom_state();
-        } else {
-            for ev in scroll_evr.read() {
-                debug!("Scroll: vertical: {}, horizontal: {}", ev.y, ev.x);
-
-                if ev.y > 0.0 {
-                    world_view.zoom_in();
-                    projection.scaling_mode = world_view.get_zoom_state();
-                } else if ev.y < 0.0 {
-                    world_view.zoom_out();
-                    projection.scaling_mode = world_view.get_zoom_state();
-                } else {
-                    error!("unkown zoom direction");
-                }
-
-                debug!("{}", world_view.zoom_factor)
-            }
-        }
+    let cursor = window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor));
+    for ev in scroll_evr.read() {
+        debug!("Scroll: vertical: {}, horizontal: {}", ev.y, ev.x);
+        let steps = match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            // touchpads scroll in pixels, many small events make up one notch
+            MouseScrollUnit::Pixel => ev.y / 40.,
+        };
+        world_view.zoom_by(steps, cursor, &settings);
     }
 }
+
+/// moves `zoom_factor` a bit closer to the target every frame, keeping the anchor in place
+fn ease_zoom(
+    time: Res<Time>,
+    settings: Res<ZoomSettings>,
+    mut world_view: Query<(&mut Transform, &mut OrthographicProjection, &mut WorldView)>,
+) {
+    let (mut transform, mut projection, mut world_view) = world_view.single_mut();
+    if world_view.zoom_factor == world_view.target_zoom {
+        return;
+    }
+
+    // eased in log space, so zooming from 1 to 2 feels as fast as from 8 to 16
+    let t = 1. - (-settings.speed * time.delta_seconds()).exp();
+    let (from, to) = (world_view.zoom_factor.ln(), world_view.target_zoom.ln());
+    let mut zoom = (from + (to - from) * t).exp();
+    if (zoom - world_view.target_zoom).abs() < 0.001 {
+        zoom = world_view.target_zoom;
+    }
+
+    // the visible area grows with the zoom, so the anchor has to be moved towards
+    // by the part of its distance that the view shrinks
+    if let Some(anchor) = world_view.zoom_anchor {
+        let center = transform.translation.truncate();
+        let shift = (anchor - center) * (1. - zoom / world_view.zoom_factor);
+        transform.translation.x += shift.x;
+        transform.translation.y += shift.y;
+        world_view.look_offset += shift;
+    }
+
+    world_view.zoom_factor = zoom;
+    projection.scaling_mode = world_view.get_zoom_state();
+}
@@ -0,0 +1,85 @@
+use bevy::{
+    input::{gamepad::GamepadButtonType, keyboard::KeyCode},
+    math::Vec2,
+};
+use rpg_game::game_plugins::actions::{
+    apply_dead_zone, combine_movement, Action, Binding, Bindings,
+};
+
+#[test]
+fn default_bindings_have_no_conflicts() {
+    assert_eq!(Bindings::default().conflicts(), vec![]);
+}
+
+#[test]
+fn rebinding_onto_a_used_key_is_a_conflict() {
+    let mut bindings = Bindings::default();
+    bindings.rebind(Action::ToggleMap, Binding::Key(KeyCode::Space));
+
+    assert_eq!(
+        bindings.conflicts(),
+        vec![(Binding::Key(KeyCode::Space), vec![Action::Interact, Action::ToggleMap])]
+    );
+    assert!(bindings.in_conflict(Action::Interact));
+    assert!(!bindings.in_conflict(Action::Pause));
+}
+
+#[test]
+fn rebinding_replaces_the_binding_of_the_same_kind() {
+    let mut bindings = Bindings::default();
+    bindings.rebind(Action::MoveUp, Binding::Key(KeyCode::Up));
+    bindings.rebind(Action::MoveUp, Binding::Button(GamepadButtonType::North));
+    assert_eq!(
+        bindings.get(Action::MoveUp),
+        &[Binding::Key(KeyCode::Up), Binding::Button(GamepadButtonType::North)]
+    );
+
+    // a key bound twice to the same action is still only bound once
+    bindings.rebind(Action::Sprint, Binding::Key(KeyCode::ShiftRight));
+    assert_eq!(
+        bindings.get(Action::Sprint),
+        &[
+            Binding::Key(KeyCode::ShiftRight),
+            Binding::Button(GamepadButtonType::West)
+        ]
+    );
+}
+
+#[test]
+fn bindings_survive_the_file() {
+    let mut bindings = Bindings::default();
+    bindings.rebind(Action::Interact, Binding::Key(KeyCode::E));
+
+    let written = ron::to_string(&bindings).unwrap();
+    assert_eq!(ron::from_str::<Bindings>(&written).unwrap(), bindings);
+}
+
+#[test]
+fn removed_actions_do_not_break_the_file() {
+    let file = "(actions: {FastMove: [Key(ShiftLeft)], Interact: [Key(E)]})";
+    let bindings = ron::from_str::<Bindings>(file).unwrap();
+
+    assert_eq!(bindings.actions.len(), 1);
+    assert_eq!(bindings.get(Action::Interact), &[Binding::Key(KeyCode::E)]);
+}
+
+#[test]
+fn sticks_start_moving_outside_the_dead_zone() {
+    assert_eq!(apply_dead_zone(Vec2::new(0.15, -0.1), 0.2), Vec2::ZERO);
+
+    let half = apply_dead_zone(Vec2::new(0., 0.6), 0.2);
+    assert!((half - Vec2::new(0., 0.5)).length() < 1e-5);
+
+    // some pads report a bit more than 1 in the corners
+    assert!((apply_dead_zone(Vec2::new(0.9, 0.9), 0.2).length() - 1.).abs() < 1e-5);
+}
+
+#[test]
+fn diagonals_are_as_fast_as_straight_lines() {
+    let diagonal = combine_movement(Vec2::new(1., -1.), Vec2::ZERO);
+    assert!((diagonal.length() - 1.).abs() < 1e-5);
+
+    // a lightly pushed stick walks slowly, the keys still go full speed
+    assert_eq!(combine_movement(Vec2::ZERO, Vec2::new(0.3, 0.)), Vec2::new(0.3, 0.));
+    assert_eq!(combine_movement(Vec2::new(0., 1.), Vec2::new(0.3, 0.)), Vec2::new(0., 1.));
+}
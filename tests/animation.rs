@@ -0,0 +1,62 @@
+use bevy::{math::Vec2, utils::HashMap};
+use rpg_game::game_plugins::{
+    animation::{Clip, Facing, SpriteAnimation, SpriteSheetDef},
+    data::load_data,
+};
+
+fn player_sheet() -> SpriteSheetDef {
+    let mut sheets: HashMap<String, SpriteSheetDef> = load_data("animations.ron").unwrap();
+    sheets.remove("player").unwrap()
+}
+
+#[test]
+fn every_clip_fits_on_its_sheet() {
+    let sheets: HashMap<String, SpriteSheetDef> = load_data("animations.ron").unwrap();
+    for (name, sheet) in sheets {
+        assert_eq!(sheet.validate(), Ok(()), "{}", name);
+    }
+}
+
+#[test]
+fn clips_off_the_sheet_are_noticed() {
+    let mut sheet = player_sheet();
+    sheet.clips.get_mut(&Clip::Chop).unwrap().row = sheet.rows - 2;
+    assert!(sheet.validate().is_err());
+
+    let mut sheet = player_sheet();
+    sheet.clips.get_mut(&Clip::Walk).unwrap().frames = sheet.columns + 1;
+    assert!(sheet.validate().is_err());
+}
+
+#[test]
+fn the_stronger_axis_decides_the_facing() {
+    assert_eq!(Facing::of(Vec2::new(3., -1.)), Facing::Right);
+    assert_eq!(Facing::of(Vec2::new(-1., -3.)), Facing::Down);
+    assert_eq!(Facing::of(Vec2::new(0.5, 2.)), Facing::Up);
+}
+
+#[test]
+fn walking_loops_and_chopping_stops_on_its_last_frame() {
+    let sheet = player_sheet();
+    let mut animation = SpriteAnimation::new("player");
+
+    animation.play(Clip::Walk);
+    let walk = sheet.clips[&Clip::Walk];
+    animation.advance(&sheet, walk.frames as f32 / walk.fps + 0.01);
+    assert_eq!(animation.frame(), 0);
+    assert!(!animation.is_busy(&sheet));
+
+    animation.play(Clip::Chop);
+    assert_eq!(animation.frame(), 0);
+    assert!(animation.is_busy(&sheet));
+    animation.advance(&sheet, 10.);
+    let chop = sheet.clips[&Clip::Chop];
+    assert_eq!(animation.frame(), chop.frames - 1);
+    assert!(!animation.is_busy(&sheet));
+
+    animation.facing = Facing::Left;
+    assert_eq!(
+        sheet.index(Clip::Chop, Facing::Left, animation.frame()),
+        (chop.row + 1) * sheet.columns + chop.frames - 1
+    );
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use bevy::math::IVec2;
+use rpg_game::{
+    consts::CHUNK_SIZE,
+    game_plugins::{
+        save::{
+            chunk_store::{
+                files::FileChunkStore,
+                region::{Region, RegionChunkStore},
+                sqlite::SqliteChunkStore,
+                ChunkRecord, ChunkStore,
+            },
+            format::{ChunkEntities, TreeSave, CHUNK_VERSION},
+        },
+        tree::TreeStage,
+        world_map::chunk_gen::TileType,
+    },
+};
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rpg_game_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn record(position: IVec2, tile: TileType, trees: usize) -> ChunkRecord {
+    let tiles = vec![tile; (CHUNK_SIZE.x * CHUNK_SIZE.y) as usize];
+    let entities = ChunkEntities {
+        version: CHUNK_VERSION,
+        unloaded_at: 12.,
+        trees: (0..trees)
+            .map(|i| TreeSave {
+                tile: (i as i32, 0),
+                stage: TreeStage::Young,
+                health: 10,
+                growth_elapsed: 3.,
+            })
+            .collect(),
+        pigs: Vec::new(),
+    };
+
+    ChunkRecord::encode(position, &tiles, &entities).unwrap()
+}
+
+// both backends have to behave exactly the same
+fn check_store(store: &mut dyn ChunkStore) {
+    assert_eq!(store.read(IVec2::new(0, 0)), Ok(None));
+
+    let first = vec![
+        record(IVec2::new(0, 0), TileType::GrassLand, 3),
+        record(IVec2::new(-4, 7), TileType::Beach, 0),
+    ];
+    store.write_batch(&first).unwrap();
+
+    let read = store.read(IVec2::new(0, 0)).unwrap().unwrap();
+    assert_eq!(read, first[0]);
+    assert_eq!(read.entities().unwrap().trees.len(), 3);
+    assert!(read.tiles().unwrap().iter().all(|tile| *tile == TileType::GrassLand));
+
+    // writing a chunk again replaces it
+    store
+        .write_batch(&[record(IVec2::new(0, 0), TileType::DeepWater, 1)])
+        .unwrap();
+    let read = store.read(IVec2::new(0, 0)).unwrap().unwrap();
+    assert_eq!(read.entities().unwrap().trees.len(), 1);
+    assert_eq!(read.tiles().unwrap()[0], TileType::DeepWater);
+
+    let mut positions = store.positions().unwrap();
+    positions.sort_by_key(|position| (position.x, position.y));
+    assert_eq!(positions, vec![IVec2::new(-4, 7), IVec2::new(0, 0)]);
+}
+
+#[test]
+fn sqlite_store() {
+    let path = temp_path("chunks.db");
+    check_store(&mut SqliteChunkStore::open(&path).unwrap());
+
+    // a second connection sees what the first one wrote
+    let mut again = SqliteChunkStore::open(&path).unwrap();
+    assert!(again.read(IVec2::new(-4, 7)).unwrap().is_some());
+    drop(again);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn file_store() {
+    let path = temp_path("chunks");
+    check_store(&mut FileChunkStore::open(&path).unwrap());
+    let _ = std::fs::remove_dir_all(&path);
+}
+
+#[test]
+fn region_store() {
+    let path = temp_path("regions");
+    let mut store = RegionChunkStore::open(&path).unwrap();
+    check_store(&mut store);
+
+    // (0, 0) and (-4, 7) live in different regions
+    let regions = store.regions().unwrap();
+    assert_eq!(regions.len(), 2);
+
+    // a flipped byte is noticed and only costs that one chunk
+    store
+        .write_batch(&[record(IVec2::new(1, 0), TileType::Beach, 2)])
+        .unwrap();
+    let (position, region_path) = regions
+        .iter()
+        .find(|(position, _)| *position == IVec2::ZERO)
+        .unwrap();
+    let mut bytes = std::fs::read(region_path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(region_path, bytes).unwrap();
+
+    let (region, problems) = Region::load(region_path, *position, true).unwrap();
+    assert_eq!(region.chunk_count(), 1);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].chunk, Some(IVec2::new(1, 0)));
+    assert!(store.read(IVec2::new(1, 0)).is_err());
+    assert!(store.read(IVec2::new(0, 0)).unwrap().is_some());
+
+    // a broken header is moved aside instead of being written over
+    std::fs::write(region_path, b"not a region").unwrap();
+    store
+        .write_batch(&[record(IVec2::new(2, 0), TileType::Beach, 0)])
+        .unwrap();
+    let corrupt = std::fs::read(region_path.with_extension("corrupt")).unwrap();
+    assert_eq!(corrupt, b"not a region");
+    assert!(store.read(IVec2::new(2, 0)).unwrap().is_some());
+    assert_eq!(store.regions().unwrap().len(), 2);
+
+    let _ = std::fs::remove_dir_all(&path);
+}
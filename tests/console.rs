@@ -0,0 +1,70 @@
+use rpg_game::game_plugins::console::{Console, ConsoleCommands};
+
+fn commands() -> ConsoleCommands {
+    let mut commands = ConsoleCommands::default();
+    commands.register("tp", "tp <x> <y>");
+    commands.register("time set", "time set <hour>");
+    commands.register("trees grow", "trees grow");
+    commands.register("spawn pig", "spawn pig <n>");
+    commands
+}
+
+#[test]
+fn commands_of_several_words_are_found() {
+    let command = commands().parse("  time set   13.5 ").unwrap();
+    assert_eq!(command.name, "time set");
+    assert_eq!(command.args, vec!["13.5"]);
+    assert_eq!(command.arg::<f32>(0), Some(13.5));
+
+    let command = commands().parse("tp 10 -4").unwrap();
+    assert!(command.is("tp"));
+    assert_eq!((command.arg::<i32>(0), command.arg::<i32>(1)), (Some(10), Some(-4)));
+
+    assert!(commands().parse("time").is_err());
+    assert!(commands().parse("spawn cow").is_err());
+}
+
+#[test]
+fn tab_completes_as_far_as_the_commands_agree() {
+    let commands = commands();
+    let mut console = Console::default();
+
+    console.input = String::from("t");
+    console.autocomplete(&commands);
+    assert_eq!(console.input, "t");
+    assert_eq!(console.lines().last().unwrap(), "time set  tp  trees grow");
+
+    console.input = String::from("tr");
+    console.autocomplete(&commands);
+    assert_eq!(console.input, "trees grow ");
+
+    console.input = String::from("x");
+    console.autocomplete(&commands);
+    assert_eq!(console.input, "x");
+}
+
+#[test]
+fn history_goes_back_and_forth() {
+    let mut console = Console::default();
+    for line in ["seed", "tp 1 2", "tp 1 2", "regen"] {
+        console.input = line.to_string();
+        assert_eq!(console.submit().as_deref(), Some(line));
+    }
+    console.input.clear();
+    assert_eq!(console.submit(), None);
+
+    // the repeated tp is only remembered once
+    console.history_back();
+    assert_eq!(console.input, "regen");
+    console.history_back();
+    assert_eq!(console.input, "tp 1 2");
+    console.history_back();
+    console.history_back();
+    assert_eq!(console.input, "seed");
+
+    console.history_forward();
+    assert_eq!(console.input, "tp 1 2");
+    console.history_forward();
+    console.history_forward();
+    assert_eq!(console.input, "");
+}
@@ -0,0 +1,35 @@
+use bevy::utils::HashMap;
+use rpg_game::game_plugins::{
+    data::load_data,
+    economy::{Price, PriceList, Wallet},
+    inventory::Item,
+};
+
+#[test]
+fn nothing_sells_for_more_than_it_costs() {
+    let prices: HashMap<Item, Price> = load_data("prices.ron").unwrap();
+    for (item, price) in prices {
+        if let (Some(buy), Some(sell)) = (price.buy, price.sell) {
+            assert!(sell <= buy, "{:?} can be bought for {} and sold for {}", item, buy, sell);
+        }
+    }
+}
+
+#[test]
+fn sell_prices_above_the_buy_price_are_lowered() {
+    let prices = PriceList::new(HashMap::from_iter([(
+        Item::Pig,
+        Price {
+            buy: Some(10),
+            sell: Some(15),
+        },
+    )]));
+    assert_eq!(prices.get(Item::Pig).sell, Some(10));
+}
+
+#[test]
+fn a_full_wallet_does_not_overflow() {
+    let mut wallet = Wallet { money: u32::MAX - 5 };
+    wallet.earn(10);
+    assert_eq!(wallet.money, u32::MAX);
+}
@@ -0,0 +1,61 @@
+use bevy::math::IVec2;
+use rpg_game::{
+    consts::CHUNK_SIZE,
+    game_plugins::world_map::{
+        chunk_gen::TileType,
+        minimap::{new_map_image, paint_map, pixel_tile, tile_color, tile_pixel, MapTiles},
+    },
+};
+
+fn filled_chunk(tile: TileType) -> Vec<TileType> {
+    vec![tile; (CHUNK_SIZE.x * CHUNK_SIZE.y) as usize]
+}
+
+fn pixel(image: &bevy::prelude::Image, x: usize, y: usize) -> [u8; 4] {
+    let width = image.texture_descriptor.size.width as usize;
+    let at = (y * width + x) * 4;
+    image.data[at..at + 4].try_into().unwrap()
+}
+
+#[test]
+fn tiles_are_looked_up_in_their_chunk() {
+    let mut map_tiles = MapTiles::default();
+    let mut tiles = filled_chunk(TileType::GrassLand);
+    // column 1, row 2 of the chunk
+    tiles[(CHUNK_SIZE.y + 2) as usize] = TileType::Beach;
+    map_tiles.insert(IVec2::new(-1, 0), tiles);
+
+    let x = -(CHUNK_SIZE.x as i32) + 1;
+    assert_eq!(map_tiles.tile(IVec2::new(x, 2)), Some(TileType::Beach));
+    assert_eq!(map_tiles.tile(IVec2::new(x, 3)), Some(TileType::GrassLand));
+    assert_eq!(map_tiles.tile(IVec2::new(0, 2)), None);
+}
+
+#[test]
+fn the_map_is_north_up_around_the_center() {
+    let mut map_tiles = MapTiles::default();
+    map_tiles.insert(IVec2::new(0, 0), filled_chunk(TileType::DeepWater));
+    map_tiles.insert(IVec2::new(-1, 0), filled_chunk(TileType::Beach));
+
+    let mut image = new_map_image(8, 8);
+    paint_map(&mut image, &map_tiles, IVec2::ZERO, 1);
+
+    assert_eq!(pixel(&image, 4, 4), tile_color(TileType::DeepWater));
+    // west of the center
+    assert_eq!(pixel(&image, 3, 4), tile_color(TileType::Beach));
+    // north is up, so the rows above the center are still in chunk (0, 0)
+    assert_eq!(pixel(&image, 4, 0), tile_color(TileType::DeepWater));
+    // and south of the center nothing was explored
+    assert_ne!(pixel(&image, 4, 5), tile_color(TileType::DeepWater));
+}
+
+#[test]
+fn waypoints_land_on_the_pixel_of_their_tile() {
+    let size = IVec2::new(640, 310);
+    for scale in [1, 2, 4, 16] {
+        for pixel in [IVec2::new(0, 0), IVec2::new(320, 155), IVec2::new(17, 301)] {
+            let tile = pixel_tile(IVec2::new(-40, 7), scale, size, pixel);
+            assert_eq!(tile_pixel(IVec2::new(-40, 7), scale, size, tile), pixel);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use bevy::math::Vec2;
+use rpg_game::game_plugins::{
+    movement::{steer, Traction},
+    seasons::Season,
+    world_map::chunk_gen::TileType,
+};
+
+#[test]
+fn speeding_up_takes_time_on_any_ground() {
+    let grass = Traction::of(TileType::GrassLand, Season::Summer);
+    let target = Vec2::new(100., 0.);
+
+    let mut velocity = Vec2::ZERO;
+    let mut steps = 0;
+    while velocity != target {
+        velocity = steer(velocity, target, grass, 1. / 64.);
+        steps += 1;
+        assert!(velocity.length() <= target.length());
+    }
+    assert!(steps > 1);
+}
+
+#[test]
+fn ice_slides_further_than_grass() {
+    let sliding = |traction: Traction| {
+        let mut velocity = Vec2::new(0., 100.);
+        let mut steps = 0;
+        while velocity != Vec2::ZERO {
+            velocity = steer(velocity, Vec2::ZERO, traction, 1. / 64.);
+            steps += 1;
+        }
+        steps
+    };
+
+    let grass = Traction::of(TileType::GrassLand, Season::Winter);
+    let ice = Traction::of(TileType::ShallowWater, Season::Winter);
+    assert!(sliding(ice) > sliding(grass) * 4);
+}
+
+#[test]
+fn water_is_slower_than_land() {
+    let grass = Traction::of(TileType::GrassLand, Season::Summer);
+    let wading = Traction::of(TileType::ShallowWater, Season::Summer);
+    let swimming = Traction::of(TileType::DeepWater, Season::Summer);
+    assert!(grass.speed > wading.speed && wading.speed > swimming.speed);
+}
@@ -0,0 +1,132 @@
+use rpg_game::game_plugins::{
+    inventory::Item,
+    save::{
+        format::{parse_save, ChunkEntities, SaveFile, CHUNK_VERSION, SAVE_VERSION},
+        migrations::{self, CHUNK_MIGRATIONS, SAVE_MIGRATIONS},
+    },
+    tree::TreeStage,
+};
+
+// every save version the game ever wrote, in `tests/fixtures/saves/v<version>.ron`
+fn fixture(version: u32) -> String {
+    let path = format!(
+        "{}/tests/fixtures/saves/v{}.ron",
+        env!("CARGO_MANIFEST_DIR"),
+        version
+    );
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("missing fixture {}: {}", path, e))
+}
+
+// all fixtures hold the same world, so every version has to load into the same data
+fn assert_fixture_world(save: &SaveFile) {
+    assert_eq!(save.version, SAVE_VERSION);
+    assert_eq!(
+        (save.world.seed, save.world.seed2, save.world.biom_seed),
+        (1234, 98765, 4242)
+    );
+
+    assert_eq!(save.player.position, (160.5, -64.));
+    assert_eq!((save.player.health, save.player.max_health), (15, 20));
+    assert_eq!(save.player.inventory, vec![(Item::Berries, 2), (Item::Wood, 7)]);
+    assert_eq!(save.money, 35);
+    assert_eq!(save.play_time, 642.25);
+
+    assert_eq!(save.chunks.len(), 2);
+    let home = &save.chunks[0];
+    assert_eq!(home.position, (0, 0));
+    assert_eq!(home.trees.len(), 2);
+    assert_eq!(home.trees[0].stage, TreeStage::Mature);
+    assert_eq!(home.trees[1].stage, TreeStage::Stump);
+    assert_eq!(home.trees[1].growth_elapsed, 12.5);
+    assert_eq!(home.pigs[0].fed_elapsed, Some(30.));
+    assert_eq!(save.chunks[1].pigs[0].growth_elapsed, Some(50.));
+}
+
+#[test]
+fn every_version_has_a_fixture() {
+    for version in 1..=SAVE_VERSION {
+        let content = fixture(version);
+        assert_eq!(SAVE_MIGRATIONS.version_of(&content), Ok(version));
+    }
+}
+
+#[test]
+fn old_saves_are_upgraded() {
+    for version in 1..=SAVE_VERSION {
+        let save = parse_save(&fixture(version))
+            .unwrap_or_else(|e| panic!("version {} does not load: {}", version, e));
+        assert_fixture_world(&save);
+    }
+}
+
+#[test]
+fn v1_keeps_its_name() {
+    let save = parse_save(&fixture(1)).unwrap();
+    assert_eq!(save.info.name, "World 1");
+    assert_eq!(save.info.game_version, "unknown");
+}
+
+#[test]
+fn current_save_survives_a_round_trip() {
+    let save = parse_save(&fixture(SAVE_VERSION)).unwrap();
+    let written = migrations::write(&save).unwrap();
+    assert_fixture_world(&parse_save(&written).unwrap());
+}
+
+#[test]
+fn newer_saves_are_refused() {
+    let content = fixture(SAVE_VERSION).replacen(
+        &format!("version: {}", SAVE_VERSION),
+        &format!("version: {}", SAVE_VERSION + 1),
+        1,
+    );
+    assert!(parse_save(&content).is_err());
+}
+
+#[test]
+fn saves_without_version_are_refused() {
+    assert!(parse_save("(money: 5)").is_err());
+}
+
+#[test]
+fn saves_before_the_clock_start_in_the_morning() {
+    let save = parse_save(&fixture(2)).unwrap();
+    assert_eq!((save.clock.day, save.clock.hour), (1, 8.));
+
+    let save = parse_save(&fixture(3)).unwrap();
+    assert_eq!((save.clock.day, save.clock.hour), (2, 21.5));
+}
+
+#[test]
+fn saves_before_the_map_explored_their_own_chunks() {
+    let save = parse_save(&fixture(3)).unwrap();
+    assert_eq!(save.explored, vec![(0, 0), (1, 0)]);
+    assert!(save.waypoints.is_empty());
+
+    let save = parse_save(&fixture(4)).unwrap();
+    assert_eq!(save.explored, vec![(-1, 0), (0, -1), (0, 0), (1, 0)]);
+    assert_eq!(save.waypoints, vec![(12, -3)]);
+}
+
+#[test]
+fn pigs_before_the_breeding_cooldown_can_breed() {
+    let save = parse_save(&fixture(4)).unwrap();
+    assert_eq!(save.chunks[0].pigs[0].breed_cooldown_elapsed, None);
+
+    let save = parse_save(&fixture(5)).unwrap();
+    assert_eq!(save.chunks[0].pigs[0].breed_cooldown_elapsed, Some(45.));
+    assert_eq!(save.chunks[1].pigs[0].breed_cooldown_elapsed, None);
+}
+
+#[test]
+fn old_chunk_entities_are_upgraded() {
+    let content = "(version: 1, unloaded_at: 4.5, trees: [], \
+        pigs: [(position: (1.0, 2.0), growth_elapsed: None, fed_elapsed: Some(3.0))])";
+    let content = CHUNK_MIGRATIONS.upgrade(content.to_string()).unwrap();
+    let entities: ChunkEntities = migrations::parse(&content).unwrap();
+
+    assert_eq!(entities.version, CHUNK_VERSION);
+    assert_eq!(entities.unloaded_at, 4.5);
+    assert_eq!(entities.pigs[0].fed_elapsed, Some(3.));
+    assert_eq!(entities.pigs[0].breed_cooldown_elapsed, None);
+}
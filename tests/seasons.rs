@@ -0,0 +1,25 @@
+use rpg_game::game_plugins::{
+    daytime::WorldClock,
+    seasons::{Season, DAYS_PER_SEASON},
+    world_map::chunk_gen::TileType,
+};
+
+#[test]
+fn seasons_follow_the_days() {
+    assert_eq!(WorldClock::new(1, 8.).season(), Season::Spring);
+    assert_eq!(Season::of_day(DAYS_PER_SEASON), Season::Spring);
+    assert_eq!(Season::of_day(DAYS_PER_SEASON + 1), Season::Summer);
+    assert_eq!(Season::of_day(3 * DAYS_PER_SEASON + 1), Season::Winter);
+    // and a new year starts in spring again
+    assert_eq!(Season::of_day(4 * DAYS_PER_SEASON + 1), Season::Spring);
+}
+
+#[test]
+fn only_winter_freezes_shallow_water() {
+    for season in [Season::Spring, Season::Summer, Season::Autumn] {
+        assert!(!TileType::ShallowWater.is_walkable_in(season));
+    }
+    assert!(TileType::ShallowWater.is_walkable_in(Season::Winter));
+    assert!(!TileType::DeepWater.is_walkable_in(Season::Winter));
+    assert!(TileType::Beach.is_walkable_in(Season::Winter));
+}
@@ -0,0 +1,36 @@
+use bevy::utils::HashMap;
+use rpg_game::game_plugins::{
+    data::{assets_path, load_data},
+    sound::{attenuation, AudioSettings, Sfx, SfxDef, VolumeChannel},
+};
+
+#[test]
+fn every_sound_file_exists() {
+    let defs: HashMap<Sfx, SfxDef> = load_data("sounds.ron").unwrap();
+    for (sfx, def) in defs {
+        assert!(!def.files.is_empty(), "{:?} has nothing to play", sfx);
+        for file in def.files {
+            assert!(assets_path().join(&file).exists(), "{:?} plays {} which is missing", sfx, file);
+        }
+    }
+}
+
+#[test]
+fn sounds_fade_out_with_distance() {
+    assert_eq!(attenuation(0.), Some(1.));
+    assert!(attenuation(100.).unwrap() > attenuation(300.).unwrap());
+    assert_eq!(attenuation(10_000.), None);
+}
+
+#[test]
+fn master_volume_scales_every_channel() {
+    let mut settings = AudioSettings::default();
+    settings.step(VolumeChannel::Master);
+    assert_eq!(settings.master, 0.);
+
+    settings.step(VolumeChannel::Master);
+    settings.step(VolumeChannel::Master);
+    settings.sfx = 0.5;
+    assert_eq!(settings.volume(VolumeChannel::Sfx), 0.25);
+    assert_eq!(settings.volume(VolumeChannel::Ambient), 0.5);
+}
@@ -0,0 +1,28 @@
+use bevy::math::IVec2;
+use rpg_game::game_plugins::tree::{TreeStage, TreeState};
+
+fn sapling(growth_elapsed: f32) -> TreeState {
+    TreeState {
+        stage: TreeStage::Sapling,
+        health: TreeStage::Sapling.max_health(),
+        growth_elapsed,
+        tile: IVec2::ZERO,
+    }
+}
+
+#[test]
+fn unloaded_trees_keep_growing() {
+    let mut tree = sapling(80.);
+    tree.advance(20.);
+    assert_eq!(tree.stage, TreeStage::Young);
+    assert_eq!(tree.growth_elapsed, 10.);
+    assert_eq!(tree.health, TreeStage::Young.max_health());
+}
+
+#[test]
+fn trees_never_grow_backwards() {
+    let mut tree = sapling(10.);
+    tree.advance(-500.);
+    assert_eq!(tree.stage, TreeStage::Sapling);
+    assert_eq!(tree.growth_elapsed, 10.);
+}
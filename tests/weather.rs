@@ -0,0 +1,46 @@
+use rpg_game::game_plugins::{
+    weather::{Weather, WeatherKind},
+    world_map::world_gen::RngJesus,
+};
+
+fn world(seed: u32) -> RngJesus {
+    RngJesus {
+        seed,
+        seed2: 98765,
+        biom_seed: 4242,
+    }
+}
+
+fn forecast(rng_jesus: &RngJesus, slots: u64) -> Vec<WeatherKind> {
+    (0..slots).map(|slot| Weather::at(rng_jesus, slot).kind).collect()
+}
+
+#[test]
+fn same_seed_same_weather() {
+    assert_eq!(forecast(&world(1234), 200), forecast(&world(1234), 200));
+    assert_ne!(forecast(&world(1234), 200), forecast(&world(4321), 200));
+}
+
+#[test]
+fn weather_changes_one_step_at_a_time() {
+    let level = |kind: WeatherKind| kind as i32;
+    let weather = forecast(&world(1234), 500);
+
+    assert_eq!(weather[0], WeatherKind::Clear);
+    for pair in weather.windows(2) {
+        assert!(
+            (level(pair[0]) - level(pair[1])).abs() <= 1,
+            "{:?} turned into {:?}",
+            pair[0],
+            pair[1]
+        );
+    }
+    for kind in [
+        WeatherKind::Clear,
+        WeatherKind::Cloudy,
+        WeatherKind::Rain,
+        WeatherKind::Storm,
+    ] {
+        assert!(weather.contains(&kind), "never saw {:?}", kind);
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::math::IVec2;
+use rpg_game::game_plugins::world_map::world_gen::RngJesus;
+
+#[test]
+fn every_chunk_gets_its_own_seed() {
+    let rng_jesus = RngJesus::default();
+    let mut seeds = Vec::new();
+    for x in -20..20 {
+        for y in -20..20 {
+            seeds.push(rng_jesus.chunk_seed(IVec2::new(x, y), 1));
+        }
+    }
+    // these used to collide when the coordinates overlapped
+    seeds.push(rng_jesus.chunk_seed(IVec2::new(-1, 0), 1));
+    seeds.push(rng_jesus.chunk_seed(IVec2::new(0, -1), 1));
+
+    let count = seeds.len();
+    seeds.sort();
+    seeds.dedup();
+    assert_eq!(seeds.len(), count - 2);
+
+    assert_ne!(
+        rng_jesus.chunk_seed(IVec2::new(3, 4), 1),
+        rng_jesus.chunk_seed(IVec2::new(3, 4), 2)
+    );
+}
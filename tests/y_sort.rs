@@ -0,0 +1,20 @@
+use rpg_game::game_plugins::y_sort::y_sort_z;
+
+#[test]
+fn lower_feet_are_drawn_on_top() {
+    let origin = 50_000.;
+    let behind_tree = y_sort_z(origin + 10., origin);
+    let tree = y_sort_z(origin, origin);
+    let in_front_of_tree = y_sort_z(origin - 0.5, origin);
+
+    assert!(behind_tree < tree);
+    assert!(tree < in_front_of_tree);
+}
+
+#[test]
+fn sorted_sprites_stay_between_the_ground_and_the_weather() {
+    for foot_y in [-1e9, -40_000., 0., 40_000., 1e9] {
+        let z = y_sort_z(foot_y, 0.);
+        assert!(z > -10. && z < 10., "{} ends up at z {}", foot_y, z);
+    }
+}